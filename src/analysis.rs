@@ -0,0 +1,383 @@
+use std::io::{self, Write};
+
+use crate::{
+    bench::Format,
+    constants::{MAX_SECRET, MIN_SECRET},
+    game::play_with,
+    io::{write, WriteArgs},
+    players::Strategy,
+};
+
+/// Most secrets an analysis will play through. The linear strategy's work
+/// grows with the square of the range, so much larger ranges would run for
+/// hours.
+pub const MAX_SECRETS: usize = 10_000;
+
+/// Settings for an exhaustive analysis run.
+#[derive(Debug, PartialEq, Eq)]
+pub struct AnalysisConfig {
+    pub min: usize,
+    pub max: usize,
+    pub format: Format,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        AnalysisConfig {
+            min: MIN_SECRET,
+            max: MAX_SECRET,
+            format: Format::Table,
+        }
+    }
+}
+
+impl AnalysisConfig {
+    /// Build an AnalysisConfig from command line arguments, e.g.
+    /// `["--min", "1", "--max", "1000", "--format", "json"]`.
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut config = AnalysisConfig::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+
+            match arg.as_str() {
+                "--min" => config.min = parse_bound(arg, value()?)?,
+                "--max" => config.max = parse_bound(arg, value()?)?,
+                "--format" => config.format = Format::parse(value()?)?,
+                other => return Err(format!("Unknown argument '{}'", other)),
+            }
+        }
+
+        if config.min > config.max {
+            return Err(format!(
+                "--min ({}) must not be greater than --max ({})",
+                config.min, config.max
+            ));
+        }
+        if range_size(config.min, config.max) > MAX_SECRETS {
+            return Err(format!(
+                "[{}, {}] holds more secrets than analyze plays through, at most {}",
+                config.min, config.max, MAX_SECRETS
+            ));
+        }
+
+        Ok(config)
+    }
+}
+
+/// Parse the value given for a command line argument as a range bound.
+pub fn parse_bound(arg: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} must be a non-negative integer, not '{}'", arg, value))
+}
+
+/// Number of secrets in the inclusive range [min, max], saturating at
+/// usize::MAX rather than overflowing for the full range.
+pub fn range_size(min: usize, max: usize) -> usize {
+    (max - min).saturating_add(1)
+}
+
+/// The fewest guesses any strategy can promise to need for a range of `n`
+/// secrets, i.e. ceil(log2 n).
+pub fn lower_bound(n: usize) -> usize {
+    match n {
+        0 | 1 => 0,
+        _ => (usize::BITS - (n - 1).leading_zeros()) as usize,
+    }
+}
+
+/// Exact results of playing one deterministic strategy against every secret
+/// in a range.
+#[derive(Debug)]
+pub struct StrategyAnalysis {
+    pub name: &'static str,
+    pub secrets: usize,
+    pub total_guesses: usize,
+    pub worst: usize,
+    /// Number of secrets found in exactly `i + 1` guesses, for each index `i`.
+    pub distribution: Vec<usize>,
+}
+
+impl StrategyAnalysis {
+    /// Play the given strategy against every secret in [min, max].
+    pub fn new(strategy: &Strategy, min: usize, max: usize) -> Self {
+        let mut distribution: Vec<usize> = Vec::new();
+
+        for secret in min..=max {
+            // built-in players never give up
+            let guesses = play_with(secret, &mut strategy.player(min, max)).unwrap();
+
+            if distribution.len() < guesses {
+                distribution.resize(guesses, 0);
+            }
+            distribution[guesses - 1] += 1;
+        }
+
+        StrategyAnalysis {
+            name: strategy.name(),
+            secrets: range_size(min, max),
+            total_guesses: distribution
+                .iter()
+                .enumerate()
+                .map(|(i, count)| (i + 1) * count)
+                .sum(),
+            worst: distribution.len(),
+            distribution,
+        }
+    }
+
+    /// Exact expected number of guesses over a uniformly chosen secret.
+    pub fn expected(&self) -> f64 {
+        self.total_guesses as f64 / self.secrets as f64
+    }
+}
+
+/// Analyse every deterministic built-in strategy over [min, max].
+pub fn run(config: &AnalysisConfig) -> Vec<StrategyAnalysis> {
+    Strategy::ALL
+        .iter()
+        .filter(|strategy| strategy.is_deterministic())
+        .map(|strategy| StrategyAnalysis::new(strategy, config.min, config.max))
+        .collect()
+}
+
+/// Write the given analyses to the write stream in the given format.
+pub fn report(
+    analyses: &[StrategyAnalysis],
+    config: &AnalysisConfig,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    let bound = lower_bound(range_size(config.min, config.max));
+
+    match config.format {
+        Format::Table => report_table(analyses, config, bound, &mut writer),
+        Format::Csv => report_csv(analyses, bound, &mut writer),
+        Format::Json => report_json(analyses, config, bound, &mut writer),
+    }
+}
+
+/// Format a distribution as `guesses:secrets` pairs, skipping empty counts.
+fn distribution_pairs(distribution: &[usize]) -> Vec<String> {
+    distribution
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .map(|(i, count)| format!("{}:{}", i + 1, count))
+        .collect()
+}
+
+fn report_table(
+    analyses: &[StrategyAnalysis],
+    config: &AnalysisConfig,
+    bound: usize,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    write(
+        &mut writer,
+        WriteArgs::Fmt(format_args!(
+            "Range [{}, {}]: {} secrets, lower bound ceil(log2 n) = {} guesses\n\n",
+            config.min,
+            config.max,
+            range_size(config.min, config.max),
+            bound,
+        )),
+    )?;
+    write(
+        &mut writer,
+        WriteArgs::Fmt(format_args!(
+            "{:<8} {:>9} {:>6} {:>9} {:>10}  {}\n",
+            "strategy",
+            "expected",
+            "worst",
+            "worst gap",
+            "mean gap",
+            "distribution (guesses:secrets)",
+        )),
+    )?;
+
+    for a in analyses {
+        write(
+            &mut writer,
+            WriteArgs::Fmt(format_args!(
+                "{:<8} {:>9.4} {:>6} {:>+9} {:>+10.4}  {}\n",
+                a.name,
+                a.expected(),
+                a.worst,
+                a.worst as i64 - bound as i64,
+                a.expected() - bound as f64,
+                distribution_pairs(&a.distribution).join(" "),
+            )),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn report_csv(
+    analyses: &[StrategyAnalysis],
+    bound: usize,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    write(
+        &mut writer,
+        WriteArgs::Str("strategy,secrets,expected,worst,lower_bound,worst_gap,distribution\n"),
+    )?;
+
+    for a in analyses {
+        write(
+            &mut writer,
+            WriteArgs::Fmt(format_args!(
+                "{},{},{:.6},{},{},{},{}\n",
+                a.name,
+                a.secrets,
+                a.expected(),
+                a.worst,
+                bound,
+                a.worst as i64 - bound as i64,
+                distribution_pairs(&a.distribution).join(";"),
+            )),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn report_json(
+    analyses: &[StrategyAnalysis],
+    config: &AnalysisConfig,
+    bound: usize,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    let entries: Vec<String> = analyses
+        .iter()
+        .map(|a| {
+            let distribution: Vec<String> =
+                a.distribution.iter().map(|count| count.to_string()).collect();
+
+            format!(
+                "{{\"strategy\":\"{}\",\"expected\":{:.6},\"worst\":{},\"worst_gap\":{},\"distribution\":[{}]}}",
+                a.name,
+                a.expected(),
+                a.worst,
+                a.worst as i64 - bound as i64,
+                distribution.join(","),
+            )
+        })
+        .collect();
+
+    write(
+        &mut writer,
+        WriteArgs::Fmt(format_args!(
+            "{{\"min\":{},\"max\":{},\"secrets\":{},\"lower_bound\":{},\"strategies\":[{}]}}\n",
+            config.min,
+            config.max,
+            range_size(config.min, config.max),
+            bound,
+            entries.join(","),
+        )),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::test_utils::setup_io;
+
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn from_args_reads_range_and_format() {
+        let config =
+            AnalysisConfig::from_args(&args(&["--min", "1", "--max", "8", "--format", "csv"]));
+
+        assert_eq!(
+            config,
+            Ok(AnalysisConfig {
+                min: 1,
+                max: 8,
+                format: Format::Csv,
+            })
+        )
+    }
+
+    #[test]
+    fn from_args_rejects_inverted_range() {
+        assert!(AnalysisConfig::from_args(&args(&["--min", "10", "--max", "1"])).is_err())
+    }
+
+    #[test]
+    fn from_args_rejects_ranges_too_big_to_play_through() {
+        let max = (MAX_SECRETS - 1).to_string();
+        let too_big = MAX_SECRETS.to_string();
+
+        assert!(AnalysisConfig::from_args(&args(&["--min", "0", "--max", &max])).is_ok());
+        assert!(AnalysisConfig::from_args(&args(&["--min", "0", "--max", &too_big])).is_err());
+        assert!(AnalysisConfig::from_args(&args(&["--max", &usize::MAX.to_string()])).is_err());
+    }
+
+    #[test]
+    fn range_size_saturates() {
+        assert_eq!(range_size(3, 3), 1);
+        assert_eq!(range_size(0, usize::MAX), usize::MAX);
+    }
+
+    #[test]
+    fn lower_bound_is_ceil_log2() {
+        assert_eq!(lower_bound(1), 0);
+        assert_eq!(lower_bound(2), 1);
+        assert_eq!(lower_bound(8), 3);
+        assert_eq!(lower_bound(9), 4);
+        assert_eq!(lower_bound(101), 7);
+    }
+
+    #[test]
+    fn linear_analysis_is_exact() {
+        let analysis = StrategyAnalysis::new(&Strategy::Linear, 1, 4);
+
+        assert_eq!(analysis.distribution, vec![1, 1, 1, 1]);
+        assert_eq!(analysis.worst, 4);
+        assert_eq!(analysis.expected(), 2.5);
+    }
+
+    #[test]
+    fn binary_analysis_meets_lower_bound() {
+        let analysis = StrategyAnalysis::new(&Strategy::Binary, MIN_SECRET, MAX_SECRET);
+
+        assert_eq!(analysis.worst, lower_bound(MAX_SECRET - MIN_SECRET + 1));
+        assert_eq!(
+            analysis.distribution.iter().sum::<usize>(),
+            analysis.secrets
+        );
+    }
+
+    #[test]
+    fn run_skips_random_strategy() {
+        let analyses = run(&AnalysisConfig::default());
+
+        assert!(analyses.iter().all(|a| a.name != Strategy::Random.name()));
+        assert_eq!(analyses.len(), Strategy::ALL.len() - 1);
+    }
+
+    #[test]
+    fn report_csv_writes_distribution_pairs() {
+        let (mut writer, _) = setup_io();
+        let config = AnalysisConfig {
+            min: 1,
+            max: 4,
+            format: Format::Csv,
+        };
+        report(&run(&config), &config, &mut writer).unwrap();
+
+        assert!(writer.written_lines[1].starts_with("linear,4,2.500000,4,2,2,1:1;2:1;3:1;4:1"));
+    }
+}
@@ -0,0 +1,561 @@
+use std::{
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    constants::{MAX_SECRET, MIN_SECRET},
+    difficulty::Settings,
+    engine::{Engine, DEFAULT_TIMEOUT},
+    game::{play_round, play_with, GameError, GameState},
+    io::{write, WriteArgs},
+    json::Value,
+    players::Strategy,
+    random::{parse_seed, NumberGenerator, SecretSource},
+};
+
+/// Number of games each strategy plays when `--games` isn't given.
+pub const DEFAULT_GAMES: usize = 1000;
+/// Most guesses a strategy may take & still count as a win when `--cap`
+/// isn't given.
+pub const DEFAULT_CAP: usize = 10;
+
+/// The formats a benchmark report can be written in.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+/// Settings for a headless benchmark run.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BenchConfig {
+    pub games: usize,
+    pub cap: usize,
+    pub format: Format,
+    /// Seed for the secrets & random players, a random one if None.
+    pub seed: Option<u64>,
+    /// Command lines of external engines to play alongside the built-in
+    /// strategies, see crate::engine::Engine
+    pub engines: Vec<String>,
+    /// Time an engine may take over each guess
+    pub timeout: Duration,
+}
+
+impl Format {
+    /// Parse a format from its command line name.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "table" => Ok(Format::Table),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            other => Err(format!("Unknown format '{}'", other)),
+        }
+    }
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        BenchConfig {
+            games: DEFAULT_GAMES,
+            cap: DEFAULT_CAP,
+            format: Format::Table,
+            seed: None,
+            engines: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl BenchConfig {
+    /// Build a BenchConfig from command line arguments, e.g.
+    /// `["--games", "500", "--cap", "7", "--format", "csv", "--seed", "42"]`.
+    /// `--engine` may be given more than once.
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut config = BenchConfig::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+
+            match arg.as_str() {
+                "--games" => config.games = parse_count(arg, value()?)?,
+                "--cap" => config.cap = parse_count(arg, value()?)?,
+                "--format" => config.format = Format::parse(value()?)?,
+                "--seed" => config.seed = Some(parse_seed(value()?)?),
+                "--engine" => config.engines.push(value()?.clone()),
+                "--timeout" => {
+                    config.timeout = Duration::from_millis(parse_count(arg, value()?)? as u64)
+                }
+                other => return Err(format!("Unknown argument '{}'", other)),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Parse the value given for a command line argument as a positive count.
+pub fn parse_count(arg: &str, value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(format!(
+            "{} must be a positive integer, not '{}'",
+            arg, value
+        )),
+    }
+}
+
+/// Summary of how one strategy did over a benchmark run.
+#[derive(Debug)]
+pub struct StrategyReport {
+    pub name: String,
+    pub games: usize,
+    pub mean: f64,
+    pub median: usize,
+    pub max: usize,
+    pub p99: usize,
+    pub win_rate: f64,
+    pub wall_time: Duration,
+    /// Why each game an engine forfeited was lost, which count as games
+    /// played but not won
+    pub forfeits: Vec<String>,
+}
+
+impl StrategyReport {
+    /// Summarise the guess counts a strategy took over a run, counting games
+    /// won within `cap` guesses as wins.
+    pub fn new(name: &str, counts: &[usize], cap: usize, wall_time: Duration) -> Self {
+        Self::with_forfeits(name, counts, Vec::new(), cap, wall_time)
+    }
+
+    /// Like `new`, for an engine that also forfeited games for the given
+    /// reasons. Guess counts are only taken from the games it finished.
+    pub fn with_forfeits(
+        name: &str,
+        counts: &[usize],
+        forfeits: Vec<String>,
+        cap: usize,
+        wall_time: Duration,
+    ) -> Self {
+        let mut sorted = counts.to_vec();
+        sorted.sort_unstable();
+
+        let games = sorted.len() + forfeits.len();
+        let total: usize = sorted.iter().sum();
+        let wins = sorted.iter().filter(|count| **count <= cap).count();
+
+        StrategyReport {
+            name: name.to_string(),
+            games,
+            mean: match sorted.len() {
+                0 => 0.0,
+                finished => total as f64 / finished as f64,
+            },
+            median: percentile(&sorted, 50.0),
+            max: *sorted.last().unwrap_or(&0),
+            p99: percentile(&sorted, 99.0),
+            win_rate: match games {
+                0 => 0.0,
+                games => wins as f64 / games as f64,
+            },
+            wall_time,
+            forfeits,
+        }
+    }
+}
+
+/// Nearest-rank percentile of an already sorted list of counts.
+fn percentile(sorted: &[usize], pct: f64) -> usize {
+    if sorted.is_empty() {
+        return 0;
+    }
+
+    let rank = (pct / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Draw the `config.games` secrets every strategy & engine in a run plays
+/// against, so they're all compared on the same games.
+pub fn draw_secrets(config: &BenchConfig, rnd: &mut NumberGenerator) -> Vec<usize> {
+    (0..config.games)
+        .map(|_| rnd.gen_secret_in(MIN_SECRET, MAX_SECRET))
+        .collect()
+}
+
+/// Play a game against each of the given secrets with every built-in
+/// strategy & report how each one did. Random players are seeded from the
+/// given generator, so a run can be repeated exactly.
+pub fn run(
+    config: &BenchConfig,
+    secrets: &[usize],
+    rnd: &mut NumberGenerator,
+) -> Vec<StrategyReport> {
+    Strategy::ALL
+        .iter()
+        .map(|strategy| {
+            let start = Instant::now();
+            let counts: Vec<usize> = secrets
+                .iter()
+                .map(|&secret| {
+                    let seed = rnd.gen_seed();
+                    let mut player = strategy.seeded_player(MIN_SECRET, MAX_SECRET, seed);
+                    // built-in players never give up
+                    play_with(secret, &mut player).unwrap()
+                })
+                .collect();
+
+            StrategyReport::new(strategy.name(), &counts, config.cap, start.elapsed())
+        })
+        .collect()
+}
+
+/// Play a game against each of the given secrets with every engine in
+/// `config.engines`, on the same terms as the built-in strategies, & report
+/// how each one did. An engine that forfeits a game is restarted for the
+/// next one. Returns Err if an engine can't be started.
+pub fn run_engines(config: &BenchConfig, secrets: &[usize]) -> Result<Vec<StrategyReport>, String> {
+    let settings = Settings {
+        min: MIN_SECRET,
+        max: MAX_SECRET,
+        max_guesses: None,
+    };
+    let start_engine = |command: &String| {
+        Engine::spawn(command, config.timeout)
+            .map_err(|foul| format!("Engine '{}' {}", command, foul))
+    };
+
+    config
+        .engines
+        .iter()
+        .map(|command| {
+            let start = Instant::now();
+            let mut engine = start_engine(command)?;
+            let name = engine.name().to_string();
+            let (mut counts, mut forfeits) = (Vec::new(), Vec::new());
+
+            for (game, &secret) in (1..).zip(secrets) {
+                // the same unbounded round the built-in players play
+                let result = engine
+                    .new_game(settings)
+                    .map_err(GameError::Forfeit)
+                    .and_then(|_| play_round(&mut GameState::unbounded(secret, None), &mut engine));
+
+                match result {
+                    Ok(count) => counts.push(count),
+                    Err(err) => {
+                        let reason = match err {
+                            GameError::Forfeit(foul) => foul.to_string(),
+                            other => format!("{:?}", other),
+                        };
+                        forfeits.push(format!("game {}: {}", game, reason));
+                        engine = start_engine(command)?;
+                    }
+                }
+            }
+
+            Ok(StrategyReport::with_forfeits(
+                &name,
+                &counts,
+                forfeits,
+                config.cap,
+                start.elapsed(),
+            ))
+        })
+        .collect()
+}
+
+/// Write the given reports to the write stream in the given format.
+pub fn report(
+    reports: &[StrategyReport],
+    format: &Format,
+    cap: usize,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    match format {
+        Format::Table => report_table(reports, cap, &mut writer),
+        Format::Csv => report_csv(reports, &mut writer),
+        Format::Json => report_json(reports, &mut writer),
+    }
+}
+
+fn report_table(reports: &[StrategyReport], cap: usize, mut writer: impl Write) -> io::Result<()> {
+    write(
+        &mut writer,
+        WriteArgs::Fmt(format_args!(
+            "{:<8} {:>7} {:>7} {:>7} {:>5} {:>5} {:>10} {:>10} {:>8}\n",
+            "strategy",
+            "games",
+            "mean",
+            "median",
+            "max",
+            "p99",
+            format!("win<={}", cap),
+            "time (ms)",
+            "forfeits",
+        )),
+    )?;
+
+    for r in reports {
+        write(
+            &mut writer,
+            WriteArgs::Fmt(format_args!(
+                "{:<8} {:>7} {:>7.2} {:>7} {:>5} {:>5} {:>9.1}% {:>10.3} {:>8}\n",
+                r.name,
+                r.games,
+                r.mean,
+                r.median,
+                r.max,
+                r.p99,
+                r.win_rate * 100.0,
+                r.wall_time.as_secs_f64() * 1000.0,
+                r.forfeits.len(),
+            )),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn report_csv(reports: &[StrategyReport], mut writer: impl Write) -> io::Result<()> {
+    write(
+        &mut writer,
+        WriteArgs::Str("strategy,games,mean,median,max,p99,win_rate,wall_time_ms,forfeits\n"),
+    )?;
+
+    for r in reports {
+        write(
+            &mut writer,
+            WriteArgs::Fmt(format_args!(
+                "{},{},{:.4},{},{},{},{:.4},{:.3},{}\n",
+                // engines name themselves, so their names need escaping
+                csv_field(&r.name),
+                r.games,
+                r.mean,
+                r.median,
+                r.max,
+                r.p99,
+                r.win_rate,
+                r.wall_time.as_secs_f64() * 1000.0,
+                r.forfeits.len(),
+            )),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Quote a CSV field if it holds a comma, quote or line break, doubling any
+/// quotes in it, as RFC 4180 describes.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn report_json(reports: &[StrategyReport], mut writer: impl Write) -> io::Result<()> {
+    let entries: Vec<String> = reports
+        .iter()
+        .map(|r| {
+            format!(
+                "{{\"strategy\":{},\"games\":{},\"mean\":{:.4},\"median\":{},\"max\":{},\"p99\":{},\"win_rate\":{:.4},\"wall_time_ms\":{:.3},\"forfeits\":{}}}",
+                // engines name themselves, so their names need escaping
+                Value::from(r.name.as_str()),
+                r.games,
+                r.mean,
+                r.median,
+                r.max,
+                r.p99,
+                r.win_rate,
+                r.wall_time.as_secs_f64() * 1000.0,
+                r.forfeits.len(),
+            )
+        })
+        .collect();
+
+    write(
+        &mut writer,
+        WriteArgs::Fmt(format_args!("[{}]\n", entries.join(","))),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::test_utils::setup_io;
+
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn from_args_uses_defaults_when_no_args_given() {
+        assert_eq!(BenchConfig::from_args(&[]), Ok(BenchConfig::default()))
+    }
+
+    #[test]
+    fn from_args_reads_games_cap_and_format() {
+        let config =
+            BenchConfig::from_args(&args(&["--games", "5", "--cap", "3", "--format", "csv"]));
+
+        assert_eq!(
+            config,
+            Ok(BenchConfig {
+                games: 5,
+                cap: 3,
+                format: Format::Csv,
+                ..BenchConfig::default()
+            })
+        )
+    }
+
+    #[test]
+    fn from_args_reads_seed() {
+        assert_eq!(
+            BenchConfig::from_args(&args(&["--seed", "42"])).map(|config| config.seed),
+            Ok(Some(42))
+        )
+    }
+
+    #[test]
+    fn from_args_reads_engines_and_timeout() {
+        let config = BenchConfig::from_args(&args(&[
+            "--engine",
+            "python3 a.py",
+            "--timeout",
+            "250",
+            "--engine",
+            "./b",
+        ]))
+        .unwrap();
+
+        assert_eq!(config.engines, ["python3 a.py", "./b"]);
+        assert_eq!(config.timeout, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn from_args_rejects_unknown_arguments() {
+        assert!(BenchConfig::from_args(&args(&["--nope"])).is_err())
+    }
+
+    #[test]
+    fn from_args_rejects_missing_or_invalid_values() {
+        assert!(BenchConfig::from_args(&args(&["--games"])).is_err());
+        assert!(BenchConfig::from_args(&args(&["--games", "0"])).is_err());
+        assert!(BenchConfig::from_args(&args(&["--format", "xml"])).is_err());
+    }
+
+    #[test]
+    fn strategy_report_summarises_counts() {
+        let counts = [4, 1, 3, 2, 10];
+        let report = StrategyReport::new("test", &counts, 3, Duration::ZERO);
+
+        assert_eq!(report.games, 5);
+        assert_eq!(report.mean, 4.0);
+        assert_eq!(report.median, 3);
+        assert_eq!(report.max, 10);
+        assert_eq!(report.p99, 10);
+        assert_eq!(report.win_rate, 0.6);
+    }
+
+    #[test]
+    fn run_reports_on_every_strategy() {
+        let config = BenchConfig {
+            games: 10,
+            ..BenchConfig::default()
+        };
+        let mut rnd = NumberGenerator::default();
+        let secrets = draw_secrets(&config, &mut rnd);
+        let reports = run(&config, &secrets, &mut rnd);
+
+        assert_eq!(reports.len(), Strategy::ALL.len());
+        assert!(reports.iter().all(|report| report.games == 10));
+    }
+
+    #[test]
+    fn run_plays_every_strategy_against_the_given_secrets() {
+        let secrets = [0, 9, 99];
+        let reports = run(
+            &BenchConfig::default(),
+            &secrets,
+            &mut NumberGenerator::default(),
+        );
+        let linear = reports
+            .iter()
+            .find(|r| r.name == Strategy::Linear.name())
+            .unwrap();
+
+        // linear search from 0 takes secret + 1 guesses
+        assert_eq!(linear.mean, (1 + 10 + 100) as f64 / 3.0);
+        assert!(reports.iter().all(|report| report.games == secrets.len()));
+    }
+
+    #[test]
+    fn run_is_repeatable_with_same_seed() {
+        let config = BenchConfig::default();
+        let means = || -> Vec<f64> {
+            let mut rnd = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, 42);
+            let secrets = draw_secrets(&config, &mut rnd);
+            run(&config, &secrets, &mut rnd)
+                .iter()
+                .map(|r| r.mean)
+                .collect()
+        };
+
+        assert_eq!(means(), means());
+    }
+
+    #[test]
+    fn forfeited_games_count_as_played_but_not_won() {
+        let forfeits = vec![String::from("game 2: exited")];
+        let report = StrategyReport::with_forfeits("engine", &[2, 4], forfeits, 3, Duration::ZERO);
+
+        assert_eq!(report.games, 3);
+        assert_eq!(report.mean, 3.0);
+        assert_eq!(report.win_rate, 1.0 / 3.0);
+    }
+
+    #[test]
+    fn report_csv_writes_header_and_one_row_per_strategy() {
+        let (mut writer, _) = setup_io();
+        let reports = [StrategyReport::new("binary", &[1, 2], 10, Duration::ZERO)];
+        report(&reports, &Format::Csv, 10, &mut writer).unwrap();
+
+        assert!(writer.written_lines[0].starts_with("strategy,games,mean"));
+        assert!(writer.written_lines[1].starts_with("binary,2,1.5000,1,2,2,1.0000"));
+    }
+
+    #[test]
+    fn report_csv_quotes_names_with_commas_or_quotes() {
+        let (mut writer, _) = setup_io();
+        let reports = [StrategyReport::new(
+            "sh \"my engine\",v2",
+            &[1],
+            10,
+            Duration::ZERO,
+        )];
+        report(&reports, &Format::Csv, 10, &mut writer).unwrap();
+
+        assert!(writer.written_lines[1].starts_with("\"sh \"\"my engine\"\",v2\",1,"));
+    }
+
+    #[test]
+    fn report_json_writes_an_array_of_objects() {
+        let (mut writer, _) = setup_io();
+        let reports = [StrategyReport::new("binary", &[1, 2], 10, Duration::ZERO)];
+        report(&reports, &Format::Json, 10, &mut writer).unwrap();
+
+        let json = &writer.written_lines[0];
+        assert!(json.starts_with("[{\"strategy\":\"binary\",\"games\":2,"));
+        assert!(json.ends_with("}]\n"));
+    }
+}
@@ -0,0 +1,590 @@
+use std::{fmt, path::PathBuf};
+
+use crate::{
+    analysis::{parse_bound, AnalysisConfig},
+    bench::{parse_count, BenchConfig},
+    constants::{MAX_SECRET, MIN_SECRET},
+    difficulty::Settings,
+    protocol::Interface,
+    random::parse_seed,
+    server::DEFAULT_PORT,
+};
+
+pub const MAIN_HELP: &str = "\
+Usage: guessing_game [COMMAND] [OPTIONS]
+
+Commands:
+  play      Play interactively (the default)
+  simulate  Benchmark the AI strategies against random secrets
+  analyze   Play every deterministic AI strategy against every secret
+  scores    Print the high scores & player stats
+  replay    Re-run a recorded game
+  serve     Host rounds for several players over TCP
+  help      Print help for a command
+
+Run `guessing_game <COMMAND> --help` for a command's options.
+";
+
+pub const PLAY_HELP: &str = "\
+Usage: guessing_game play [OPTIONS]
+
+Play interactively. Rounds are played at a difficulty chosen from the menu,
+unless a range or guess limit is given.
+
+Options:
+  --min N           Lowest possible secret
+  --max N           Highest possible secret
+  --max-guesses N   Most guesses allowed each round
+  --seed N          Seed secrets are generated from
+                    (default: $GUESSING_GAME_SEED, else random)
+  --player NAME     Name recorded on the scoreboard, instead of asking
+  --scores PATH     Scoreboard file (default: $GUESSING_GAME_SCORES,
+                    else $XDG_DATA_HOME/guessing_game/scores.tsv)
+  --record PATH     Record every round to PATH, to watch with `replay`
+  --protocol text|jsonl
+                    Talk to a person (the default) or to another program
+                    with one JSON object per line, see the README
+  -h, --help        Print this help
+";
+
+pub const SIMULATE_HELP: &str = "\
+Usage: guessing_game simulate [OPTIONS]
+
+Play every AI strategy against the same random secrets & report how each did.
+
+Options:
+  --games N                   Games each strategy plays (default: 1000)
+  --cap N                     Most guesses that still count as a win (default: 10)
+  --format table|csv|json     Report format (default: table)
+  --seed N                    Seed secrets & random players are generated from
+  --engine CMD                Also play the external guesser run by CMD, e.g.
+                              \"python3 engine.py\", see the README; may be
+                              given more than once
+  --timeout MS                Time an engine may take per guess (default: 1000)
+  -h, --help                  Print this help
+";
+
+pub const ANALYZE_HELP: &str = "\
+Usage: guessing_game analyze [OPTIONS]
+
+Play every deterministic AI strategy against every secret in a range &
+report exact results against the ceil(log2 n) lower bound.
+
+Options:
+  --min N                     Lowest secret (default: 0)
+  --max N                     Highest secret (default: 100); the range may
+                              hold at most 10000 secrets
+  --format table|csv|json     Report format (default: table)
+  -h, --help                  Print this help
+";
+
+pub const SCORES_HELP: &str = "\
+Usage: guessing_game scores [OPTIONS]
+
+Print the high scores for each difficulty & every player's stats.
+
+Options:
+  --scores PATH     Scoreboard file (default: $GUESSING_GAME_SCORES,
+                    else $XDG_DATA_HOME/guessing_game/scores.tsv)
+  -h, --help        Print this help
+";
+
+pub const REPLAY_HELP: &str = "\
+Usage: guessing_game replay FILE [OPTIONS]
+
+Re-run the rounds recorded in FILE with `play --record FILE`, showing each
+as it was played.
+
+Options:
+  --speed N         Play back N times faster, or 0 to skip the pauses
+                    (default: 1)
+  -h, --help        Print this help
+";
+
+pub const SERVE_HELP: &str = "\
+Usage: guessing_game serve [OPTIONS]
+
+Host rounds for several players over TCP, joined with e.g. `nc localhost 7878`
+or `telnet localhost 7878`. Everyone guesses the same secret at once & the
+first to find it wins. Rounds are played at normal difficulty unless a range
+or guess limit is given.
+
+Options:
+  --port N          Port to listen on, on localhost only (default: 7878)
+  --min N           Lowest possible secret
+  --max N           Highest possible secret
+  --max-guesses N   Most guesses each player is allowed each round
+  --seed N          Seed secrets are generated from
+                    (default: $GUESSING_GAME_SEED, else random)
+  -h, --help        Print this help
+";
+
+/// What the program was asked to do on the command line.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Play(PlayConfig),
+    Simulate(BenchConfig),
+    Analyze(AnalysisConfig),
+    Scores(ScoresConfig),
+    Replay(ReplayConfig),
+    Serve(ServeConfig),
+    /// Print the given help text
+    Help(&'static str),
+}
+
+/// Settings for an interactive session.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PlayConfig {
+    /// Settings every round is played with, instead of choosing a difficulty
+    pub settings: Option<Settings>,
+    pub seed: Option<u64>,
+    pub player: Option<String>,
+    pub scores: Option<PathBuf>,
+    /// File to record every round to
+    pub record: Option<PathBuf>,
+    pub protocol: Interface,
+}
+
+/// Settings for printing the scoreboard.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ScoresConfig {
+    pub scores: Option<PathBuf>,
+}
+
+/// Settings for replaying a recorded game.
+#[derive(Debug, PartialEq)]
+pub struct ReplayConfig {
+    pub path: PathBuf,
+    /// How many times faster than recorded to play back, or None to skip
+    /// the pauses
+    pub speed: Option<f64>,
+}
+
+/// Settings for hosting a multiplayer server.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ServeConfig {
+    pub port: u16,
+    /// Settings every round is played with, instead of normal difficulty
+    pub settings: Option<Settings>,
+    pub seed: Option<u64>,
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        ServeConfig {
+            port: DEFAULT_PORT,
+            settings: None,
+            seed: None,
+        }
+    }
+}
+
+/// Why the command line couldn't be parsed, along with the help text for
+/// the command it was meant for.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UsageError {
+    pub reason: String,
+    pub help: &'static str,
+}
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n\n{}", self.reason, self.help)
+    }
+}
+
+/// Parse the command line arguments, not including the program name, e.g.
+/// `["play", "--max", "1000"]`. With no command, options are taken to be
+/// for `play`. `bench` is accepted as another name for `simulate`.
+pub fn parse(args: &[String]) -> Result<Command, UsageError> {
+    let (command, args) = match args.split_first() {
+        Some((command, rest)) if !command.starts_with('-') => (command.as_str(), rest),
+        // with no command given, `--help` is asking about the program itself
+        Some((flag, [])) if flag == "--help" || flag == "-h" => {
+            return Ok(Command::Help(MAIN_HELP))
+        }
+        _ => ("play", args),
+    };
+
+    let help = match command {
+        "play" => PLAY_HELP,
+        "simulate" | "bench" => SIMULATE_HELP,
+        "analyze" => ANALYZE_HELP,
+        "scores" => SCORES_HELP,
+        "replay" => REPLAY_HELP,
+        "serve" => SERVE_HELP,
+        "help" => return help_for(args.first().map(String::as_str)),
+        other => {
+            return Err(UsageError {
+                reason: format!("Unknown command '{}'", other),
+                help: MAIN_HELP,
+            })
+        }
+    };
+
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        return Ok(Command::Help(help));
+    }
+
+    let command = match command {
+        "play" => parse_play(args).map(Command::Play),
+        "simulate" | "bench" => BenchConfig::from_args(args).map(Command::Simulate),
+        "analyze" => AnalysisConfig::from_args(args).map(Command::Analyze),
+        "scores" => parse_scores(args).map(Command::Scores),
+        "serve" => parse_serve(args).map(Command::Serve),
+        _ => parse_replay(args).map(Command::Replay),
+    };
+
+    command.map_err(|reason| UsageError { reason, help })
+}
+
+fn help_for(command: Option<&str>) -> Result<Command, UsageError> {
+    match command {
+        None => Ok(Command::Help(MAIN_HELP)),
+        Some("play") => Ok(Command::Help(PLAY_HELP)),
+        Some("simulate") | Some("bench") => Ok(Command::Help(SIMULATE_HELP)),
+        Some("analyze") => Ok(Command::Help(ANALYZE_HELP)),
+        Some("scores") => Ok(Command::Help(SCORES_HELP)),
+        Some("replay") => Ok(Command::Help(REPLAY_HELP)),
+        Some("serve") => Ok(Command::Help(SERVE_HELP)),
+        Some(other) => Err(UsageError {
+            reason: format!("Unknown command '{}'", other),
+            help: MAIN_HELP,
+        }),
+    }
+}
+
+fn parse_play(args: &[String]) -> Result<PlayConfig, String> {
+    let mut config = PlayConfig::default();
+    let (mut min, mut max, mut max_guesses) = (None, None, None);
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+
+        match arg.as_str() {
+            "--min" => min = Some(parse_bound(arg, value()?)?),
+            "--max" => max = Some(parse_bound(arg, value()?)?),
+            "--max-guesses" => max_guesses = Some(parse_count(arg, value()?)?),
+            "--seed" => config.seed = Some(parse_seed(value()?)?),
+            "--player" => config.player = Some(value()?.clone()),
+            "--scores" => config.scores = Some(PathBuf::from(value()?)),
+            "--record" => config.record = Some(PathBuf::from(value()?)),
+            "--protocol" => config.protocol = Interface::parse(value()?)?,
+            other => return Err(format!("Unknown argument '{}'", other)),
+        }
+    }
+
+    // JSON Lines sessions are for programs, so they don't go on the
+    // scoreboard or get recorded
+    if config.protocol == Interface::Jsonl {
+        let unsupported = [
+            ("--player", config.player.is_some()),
+            ("--scores", config.scores.is_some()),
+            ("--record", config.record.is_some()),
+        ];
+        if let Some((flag, _)) = unsupported.iter().find(|(_, given)| *given) {
+            return Err(format!("{} can't be used with --protocol jsonl", flag));
+        }
+    }
+
+    config.settings = settings_from(min, max, max_guesses)?;
+
+    Ok(config)
+}
+
+fn parse_serve(args: &[String]) -> Result<ServeConfig, String> {
+    let mut config = ServeConfig::default();
+    let (mut min, mut max, mut max_guesses) = (None, None, None);
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+
+        match arg.as_str() {
+            "--port" => {
+                let port = value()?;
+                config.port = port
+                    .parse()
+                    .map_err(|_| format!("--port must be a port number, not '{}'", port))?;
+            }
+            "--min" => min = Some(parse_bound(arg, value()?)?),
+            "--max" => max = Some(parse_bound(arg, value()?)?),
+            "--max-guesses" => max_guesses = Some(parse_count(arg, value()?)?),
+            "--seed" => config.seed = Some(parse_seed(value()?)?),
+            other => return Err(format!("Unknown argument '{}'", other)),
+        }
+    }
+
+    config.settings = settings_from(min, max, max_guesses)?;
+    Ok(config)
+}
+
+/// The Settings given by `--min`, `--max` & `--max-guesses`, if any were,
+/// with defaults for those that weren't.
+fn settings_from(
+    min: Option<usize>,
+    max: Option<usize>,
+    max_guesses: Option<usize>,
+) -> Result<Option<Settings>, String> {
+    if min.is_none() && max.is_none() && max_guesses.is_none() {
+        return Ok(None);
+    }
+
+    let settings = Settings {
+        min: min.unwrap_or(MIN_SECRET),
+        max: max.unwrap_or(MAX_SECRET),
+        max_guesses,
+    };
+    if settings.min > settings.max {
+        return Err(format!(
+            "--min ({}) must not be greater than --max ({})",
+            settings.min, settings.max
+        ));
+    }
+
+    Ok(Some(settings))
+}
+
+fn parse_scores(args: &[String]) -> Result<ScoresConfig, String> {
+    match args {
+        [] => Ok(ScoresConfig::default()),
+        [flag, path] if flag == "--scores" => Ok(ScoresConfig {
+            scores: Some(PathBuf::from(path)),
+        }),
+        [flag] if flag == "--scores" => Err(String::from("Missing value for --scores")),
+        _ => Err(format!("Unknown argument '{}'", args[0])),
+    }
+}
+
+fn parse_replay(args: &[String]) -> Result<ReplayConfig, String> {
+    let (mut path, mut speed) = (None, Some(1.0));
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--speed" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))?;
+                speed = match value.parse::<f64>() {
+                    Ok(0.0) => None,
+                    Ok(speed) if speed.is_finite() && speed > 0.0 => Some(speed),
+                    _ => {
+                        return Err(format!(
+                            "--speed must be a positive number or 0, not '{}'",
+                            value
+                        ))
+                    }
+                };
+            }
+            other if other.starts_with('-') || path.is_some() => {
+                return Err(format!("Unexpected argument '{}'", other))
+            }
+            other => path = Some(PathBuf::from(other)),
+        }
+    }
+
+    match path {
+        Some(path) => Ok(ReplayConfig { path, speed }),
+        None => Err(String::from("Missing the recorded game to replay")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bench::Format;
+
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn no_args_plays_with_defaults() {
+        assert_eq!(parse(&[]), Ok(Command::Play(PlayConfig::default())))
+    }
+
+    #[test]
+    fn options_without_command_are_for_play() {
+        assert_eq!(
+            parse(&args(&["--scores", "s.tsv", "--seed", "3"])),
+            Ok(Command::Play(PlayConfig {
+                seed: Some(3),
+                scores: Some(PathBuf::from("s.tsv")),
+                ..PlayConfig::default()
+            }))
+        )
+    }
+
+    #[test]
+    fn play_reads_range_limit_and_player() {
+        let command = parse(&args(&[
+            "play",
+            "--max",
+            "1000",
+            "--max-guesses",
+            "12",
+            "--player",
+            "ada",
+        ]));
+
+        assert_eq!(
+            command,
+            Ok(Command::Play(PlayConfig {
+                settings: Some(Settings {
+                    min: MIN_SECRET,
+                    max: 1000,
+                    max_guesses: Some(12),
+                }),
+                player: Some(String::from("ada")),
+                ..PlayConfig::default()
+            }))
+        )
+    }
+
+    #[test]
+    fn play_rejects_inverted_range() {
+        let err = parse(&args(&["play", "--min", "10", "--max", "1"])).unwrap_err();
+
+        assert_eq!(err.help, PLAY_HELP);
+    }
+
+    #[test]
+    fn play_accepts_ranges_up_to_usize_max() {
+        let max = usize::MAX.to_string();
+
+        assert_eq!(
+            parse(&args(&["play", "--max", &max])),
+            Ok(Command::Play(PlayConfig {
+                settings: Some(Settings {
+                    min: MIN_SECRET,
+                    max: usize::MAX,
+                    max_guesses: None,
+                }),
+                ..PlayConfig::default()
+            }))
+        )
+    }
+
+    #[test]
+    fn play_reads_protocol() {
+        assert_eq!(
+            parse(&args(&["play", "--protocol", "jsonl", "--max", "50"])),
+            Ok(Command::Play(PlayConfig {
+                settings: Some(Settings {
+                    min: MIN_SECRET,
+                    max: 50,
+                    max_guesses: None,
+                }),
+                protocol: Interface::Jsonl,
+                ..PlayConfig::default()
+            }))
+        );
+        assert!(parse(&args(&["play", "--protocol", "xml"])).is_err());
+        assert!(parse(&args(&["play", "--protocol", "jsonl", "--record", "r.log"])).is_err());
+    }
+
+    #[test]
+    fn simulate_and_bench_read_bench_options() {
+        let expected = Ok(Command::Simulate(BenchConfig {
+            games: 5,
+            format: Format::Json,
+            ..BenchConfig::default()
+        }));
+
+        assert_eq!(
+            parse(&args(&["simulate", "--games", "5", "--format", "json"])),
+            expected
+        );
+        assert_eq!(
+            parse(&args(&["bench", "--games", "5", "--format", "json"])),
+            expected
+        );
+    }
+
+    #[test]
+    fn help_is_given_for_each_command() {
+        assert_eq!(parse(&args(&["--help"])), Ok(Command::Help(MAIN_HELP)));
+        assert_eq!(parse(&args(&["help"])), Ok(Command::Help(MAIN_HELP)));
+        assert_eq!(
+            parse(&args(&["help", "scores"])),
+            Ok(Command::Help(SCORES_HELP))
+        );
+        assert_eq!(
+            parse(&args(&["simulate", "--games", "5", "-h"])),
+            Ok(Command::Help(SIMULATE_HELP))
+        );
+        assert_eq!(
+            parse(&args(&["play", "--help"])),
+            Ok(Command::Help(PLAY_HELP))
+        );
+    }
+
+    #[test]
+    fn replay_needs_exactly_one_file() {
+        assert_eq!(
+            parse(&args(&["replay", "game.log"])),
+            Ok(Command::Replay(ReplayConfig {
+                path: PathBuf::from("game.log"),
+                speed: Some(1.0),
+            }))
+        );
+        assert!(parse(&args(&["replay"])).is_err());
+        assert!(parse(&args(&["replay", "a", "b"])).is_err());
+    }
+
+    #[test]
+    fn replay_reads_speed() {
+        assert_eq!(
+            parse(&args(&["replay", "--speed", "4", "game.log"])),
+            Ok(Command::Replay(ReplayConfig {
+                path: PathBuf::from("game.log"),
+                speed: Some(4.0),
+            }))
+        );
+        assert_eq!(
+            parse(&args(&["replay", "game.log", "--speed", "0"])),
+            Ok(Command::Replay(ReplayConfig {
+                path: PathBuf::from("game.log"),
+                speed: None,
+            }))
+        );
+        assert!(parse(&args(&["replay", "game.log", "--speed", "-2"])).is_err());
+    }
+
+    #[test]
+    fn serve_reads_port_and_settings() {
+        assert_eq!(
+            parse(&args(&["serve"])),
+            Ok(Command::Serve(ServeConfig::default()))
+        );
+        assert_eq!(
+            parse(&args(&["serve", "--port", "4000", "--max-guesses", "5"])),
+            Ok(Command::Serve(ServeConfig {
+                port: 4000,
+                settings: Some(Settings {
+                    min: MIN_SECRET,
+                    max: MAX_SECRET,
+                    max_guesses: Some(5),
+                }),
+                seed: None,
+            }))
+        );
+        assert!(parse(&args(&["serve", "--port", "70000"])).is_err());
+    }
+
+    #[test]
+    fn unknown_commands_are_rejected_with_main_help() {
+        let err = parse(&args(&["dance"])).unwrap_err();
+
+        assert_eq!(err.help, MAIN_HELP);
+    }
+}
@@ -0,0 +1,333 @@
+use std::io::Write;
+
+use crate::{
+    analysis::{lower_bound, range_size},
+    feedback::render,
+    game::{GameError, GameState},
+    hints::{self, HintKind},
+    io::{write, WriteArgs},
+};
+
+/// What the game should do after running a SlashCommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandAction {
+    /// Keep prompting for guesses
+    Continue,
+    /// End the round, revealing the secret
+    GiveUp,
+    /// Leave the round to be saved & finished later
+    Save,
+}
+
+/// Runs a SlashCommand against the round's state, given whatever followed
+/// the command's name, writing anything it has to say to the write stream.
+pub type CommandHandler =
+    fn(&mut GameState, &str, &mut dyn Write) -> Result<CommandAction, GameError>;
+
+/// A command a player can enter instead of a guess, e.g. `/history`.
+pub struct SlashCommand {
+    /// Name entered after the slash
+    pub name: &'static str,
+    /// One line description shown by `/help`
+    pub help: &'static str,
+    pub run: CommandHandler,
+}
+
+/// Every command available during a round, in the order `/help` lists them.
+/// Add a command by adding it here.
+pub const COMMANDS: &[SlashCommand] = &[
+    SlashCommand {
+        name: "help",
+        help: "list these commands",
+        run: help,
+    },
+    SlashCommand {
+        name: "hint",
+        help: "get a hint, at a cost to your score",
+        run: hint,
+    },
+    SlashCommand {
+        name: "history",
+        help: "list your guesses so far",
+        run: history,
+    },
+    SlashCommand {
+        name: "range",
+        help: "show where the secret must be",
+        run: range,
+    },
+    SlashCommand {
+        name: "stats",
+        help: "show how the round is going",
+        run: stats,
+    },
+    SlashCommand {
+        name: "save",
+        help: "save the round to finish later",
+        run: save,
+    },
+    SlashCommand {
+        name: "giveup",
+        help: "end the round & reveal the secret",
+        run: give_up,
+    },
+];
+
+/// Split input like `/save game.txt` into the command's name & the rest of
+/// the input, or None if the input isn't a command.
+pub fn parse(input: &str) -> Option<(&str, &str)> {
+    let command = input.strip_prefix('/')?;
+    match command.split_once(char::is_whitespace) {
+        Some((name, args)) => Some((name, args.trim())),
+        None => Some((command, "")),
+    }
+}
+
+/// Find the command with the given name.
+pub fn find(name: &str) -> Option<&'static SlashCommand> {
+    COMMANDS.iter().find(|command| command.name == name)
+}
+
+/// Run the command entered as `input`, telling the player if there's no such
+/// command. Returns None if the input isn't a command at all.
+pub fn run(
+    input: &str,
+    state: &mut GameState,
+    writer: &mut dyn Write,
+) -> Option<Result<CommandAction, GameError>> {
+    let (name, args) = parse(input)?;
+
+    Some(match find(name) {
+        Some(command) => (command.run)(state, args, writer),
+        None => write(
+            writer,
+            WriteArgs::Fmt(format_args!(
+                "There's no /{} command, enter /help to see the commands.\n\n",
+                name
+            )),
+        )
+        .map(|_| CommandAction::Continue)
+        .map_err(GameError::from),
+    })
+}
+
+fn help(_: &mut GameState, _: &str, writer: &mut dyn Write) -> Result<CommandAction, GameError> {
+    write(&mut *writer, WriteArgs::Str("Commands:\n"))?;
+    for command in COMMANDS {
+        write(
+            &mut *writer,
+            WriteArgs::Fmt(format_args!("  /{:<10}{}\n", command.name, command.help)),
+        )?;
+    }
+    write(writer, WriteArgs::Str("  quit       quit playing\n\n"))?;
+
+    Ok(CommandAction::Continue)
+}
+
+fn hint(
+    state: &mut GameState,
+    args: &str,
+    writer: &mut dyn Write,
+) -> Result<CommandAction, GameError> {
+    match HintKind::parse(args) {
+        Some(kind) => {
+            let hint = state.hint(kind);
+            write(
+                writer,
+                WriteArgs::Fmt(format_args!(
+                    "{} (-{} points)\n\n",
+                    hints::render(&hint),
+                    kind.cost()
+                )),
+            )?;
+        }
+        None => {
+            if !args.is_empty() {
+                write(
+                    &mut *writer,
+                    WriteArgs::Fmt(format_args!("There's no '{}' hint.\n", args)),
+                )?;
+            }
+            write(&mut *writer, WriteArgs::Str("Hints, e.g. /hint parity:\n"))?;
+            for kind in HintKind::ALL {
+                write(
+                    &mut *writer,
+                    WriteArgs::Fmt(format_args!(
+                        "  {:<9}{} (-{} points)\n",
+                        kind.name(),
+                        kind.describe(),
+                        kind.cost()
+                    )),
+                )?;
+            }
+            write(writer, WriteArgs::Str("\n"))?;
+        }
+    }
+
+    Ok(CommandAction::Continue)
+}
+
+fn history(
+    state: &mut GameState,
+    _: &str,
+    writer: &mut dyn Write,
+) -> Result<CommandAction, GameError> {
+    if state.history().is_empty() {
+        write(writer, WriteArgs::Str("No guesses yet.\n\n"))?;
+        return Ok(CommandAction::Continue);
+    }
+
+    write(&mut *writer, WriteArgs::Str("Your guesses so far:\n"))?;
+    for (i, feedback) in state.history().iter().enumerate() {
+        write(
+            &mut *writer,
+            WriteArgs::Fmt(format_args!("{:>3}. {}\n", i + 1, render(feedback))),
+        )?;
+    }
+    write(writer, WriteArgs::Str("\n"))?;
+
+    Ok(CommandAction::Continue)
+}
+
+fn range(
+    state: &mut GameState,
+    _: &str,
+    writer: &mut dyn Write,
+) -> Result<CommandAction, GameError> {
+    let (low, high) = state.interval();
+    write(
+        writer,
+        WriteArgs::Fmt(format_args!("The secret is in [{}, {}].\n\n", low, high)),
+    )?;
+
+    Ok(CommandAction::Continue)
+}
+
+fn stats(
+    state: &mut GameState,
+    _: &str,
+    writer: &mut dyn Write,
+) -> Result<CommandAction, GameError> {
+    let (low, high) = state.interval();
+    let left = match state.guesses_left() {
+        Some(left) => format!("{} left", left),
+        None => String::from("no limit"),
+    };
+    let possible = range_size(low, high);
+
+    write(
+        writer,
+        WriteArgs::Fmt(format_args!(
+            "Guesses made: {} ({}). Numbers still possible: {}. A binary search would need at most {} more.\n\n",
+            state.guesses(),
+            left,
+            possible,
+            lower_bound(possible.saturating_add(1)),
+        )),
+    )?;
+
+    Ok(CommandAction::Continue)
+}
+
+fn save(_: &mut GameState, _: &str, _: &mut dyn Write) -> Result<CommandAction, GameError> {
+    Ok(CommandAction::Save)
+}
+
+fn give_up(_: &mut GameState, _: &str, _: &mut dyn Write) -> Result<CommandAction, GameError> {
+    Ok(CommandAction::GiveUp)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{difficulty::Settings, io::test_utils::setup_io};
+
+    use super::*;
+
+    #[test]
+    fn parse_splits_name_and_args() {
+        assert_eq!(parse("/history"), Some(("history", "")));
+        assert_eq!(parse("/save  my game "), Some(("save", "my game")));
+        assert_eq!(parse("history"), None);
+    }
+
+    #[test]
+    fn command_names_are_unique() {
+        for (i, command) in COMMANDS.iter().enumerate() {
+            assert!(COMMANDS[i + 1..].iter().all(|c| c.name != command.name));
+        }
+    }
+
+    #[test]
+    fn help_lists_every_command() {
+        let (mut writer, _) = setup_io();
+        let mut state = GameState::new(40, Settings::default());
+        run("/help", &mut state, &mut writer);
+
+        for command in COMMANDS {
+            let name = format!("/{}", command.name);
+            assert!(writer.written_lines.iter().any(|line| line.contains(&name)));
+        }
+    }
+
+    #[test]
+    fn unknown_command_points_to_help() {
+        let (mut writer, _) = setup_io();
+        let mut state = GameState::new(40, Settings::default());
+        let action = run("/dance", &mut state, &mut writer).unwrap().unwrap();
+
+        assert_eq!(action, CommandAction::Continue);
+        assert!(writer.written_lines[0].contains("/help"));
+    }
+
+    #[test]
+    fn hint_is_recorded_on_state() {
+        let (mut writer, _) = setup_io();
+        let mut state = GameState::new(40, Settings::default());
+        run("/hint parity", &mut state, &mut writer);
+
+        assert_eq!(state.hint_penalty(), HintKind::Parity.cost());
+        assert!(writer.written_lines[0].starts_with("The secret is even."));
+    }
+
+    #[test]
+    fn hint_without_kind_lists_kinds_for_free() {
+        let (mut writer, _) = setup_io();
+        let mut state = GameState::new(40, Settings::default());
+        run("/hint", &mut state, &mut writer);
+
+        assert_eq!(state.hint_penalty(), 0);
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains("next")));
+    }
+
+    #[test]
+    fn stats_counts_numbers_still_possible() {
+        let (mut writer, _) = setup_io();
+        let mut state = GameState::new(40, Settings::default());
+        state.guess(50);
+        run("/stats", &mut state, &mut writer);
+
+        assert!(writer.written_lines[0].contains("Numbers still possible: 50."));
+    }
+
+    #[test]
+    fn stats_handles_ranges_ending_near_usize_max() {
+        let (mut writer, _) = setup_io();
+        let settings = Settings {
+            min: 0,
+            max: usize::MAX - 1,
+            max_guesses: None,
+        };
+        let mut state = GameState::new(usize::MAX - 1, settings);
+        run("/stats", &mut state, &mut writer);
+        state.guess(0);
+        run("/stats", &mut state, &mut writer);
+
+        let possible = format!("Numbers still possible: {}.", usize::MAX);
+        assert!(writer.written_lines[0].contains(&possible));
+        assert!(writer.written_lines[0].contains("at most 64 more"));
+        assert!(writer.written_lines[1].contains(&format!("possible: {}.", usize::MAX - 1)));
+    }
+}
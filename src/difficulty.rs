@@ -0,0 +1,268 @@
+use std::io::{BufRead, Result, Write};
+
+use crate::{
+    analysis::{lower_bound, range_size},
+    constants::{MAX_SECRET, MIN_SECRET},
+    io::{prompt, write, WriteArgs},
+};
+
+/// The range a game's secret is chosen from & how many guesses the guesser
+/// gets to find it, if limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Settings {
+    pub min: usize,
+    pub max: usize,
+    pub max_guesses: Option<usize>,
+}
+
+impl Settings {
+    /// Fewest guesses any strategy can promise to find the secret in, i.e.
+    /// ceil(log2 n) for n possible secrets, but at least one.
+    pub fn optimal_guesses(&self) -> usize {
+        lower_bound(range_size(self.min, self.max)).max(1)
+    }
+
+    /// How much harder than an unlimited round these Settings are, from 1
+    /// for no guess limit up to 2 for a limit no more than the optimal
+    /// number of guesses.
+    pub fn multiplier(&self) -> f64 {
+        match self.max_guesses {
+            Some(limit) => 1.0 + (self.optimal_guesses() as f64 / limit as f64).min(1.0),
+            None => 1.0,
+        }
+    }
+}
+
+impl Default for Settings {
+    /// Settings with MIN & MAX values from crate::constants & no guess limit
+    fn default() -> Self {
+        Settings {
+            min: MIN_SECRET,
+            max: MAX_SECRET,
+            max_guesses: None,
+        }
+    }
+}
+
+/// The preset difficulties a game can be played at, plus Custom for
+/// settings chosen by the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Custom(Settings),
+}
+
+impl Difficulty {
+    /// Every preset difficulty, in the order they should be offered.
+    pub const PRESETS: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// The preset difficulty with the given name & Settings, else a Custom
+    /// one with the Settings, e.g. when reading a difficulty back from a
+    /// file.
+    pub fn from_parts(name: &str, settings: Settings) -> Self {
+        Self::PRESETS
+            .into_iter()
+            .find(|d| d.name() == name && d.settings() == settings)
+            .unwrap_or(Difficulty::Custom(settings))
+    }
+
+    /// Short lowercase name used when showing the difficulty.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Custom(_) => "custom",
+        }
+    }
+
+    /// The Settings a game at this difficulty is played with.
+    pub fn settings(&self) -> Settings {
+        match self {
+            Difficulty::Easy => Settings {
+                min: 0,
+                max: 10,
+                max_guesses: None,
+            },
+            Difficulty::Normal => Settings {
+                min: MIN_SECRET,
+                max: MAX_SECRET,
+                max_guesses: Some(10),
+            },
+            Difficulty::Hard => Settings {
+                min: 0,
+                max: 1000,
+                max_guesses: Some(10),
+            },
+            Difficulty::Custom(settings) => *settings,
+        }
+    }
+
+    /// Describe this difficulty's range & guess limit, e.g.
+    /// `"normal: [0,100], 10 guesses"`.
+    pub fn describe(&self) -> String {
+        let settings = self.settings();
+        let limit = match settings.max_guesses {
+            Some(count) => format!("{} guesses", count),
+            None => String::from("unlimited guesses"),
+        };
+
+        format!(
+            "{}: [{},{}], {}",
+            self.name(),
+            settings.min,
+            settings.max,
+            limit
+        )
+    }
+}
+
+/// Prompt the user for the range & guess limit of a Custom difficulty,
+/// asking again until each answer is valid. Returns Err if either stream
+/// fails.
+pub fn prompt_custom(mut writer: impl Write, mut reader: impl BufRead) -> Result<Difficulty> {
+    let min = prompt_number("Lowest possible secret?\n", &mut writer, &mut reader)?;
+    let max = loop {
+        let max = prompt_number("Highest possible secret?\n", &mut writer, &mut reader)?;
+        if max >= min {
+            break max;
+        }
+        write(
+            &mut writer,
+            WriteArgs::Fmt(format_args!(
+                "The highest secret can't be lower than {}.\n",
+                min
+            )),
+        )?;
+    };
+    let max_guesses = loop {
+        write(
+            &mut writer,
+            WriteArgs::Str("Most guesses allowed? (leave empty for unlimited)\n"),
+        )?;
+        let answer = prompt(&mut writer, &mut reader)?;
+        if answer.is_empty() {
+            break None;
+        }
+        match answer.parse() {
+            Ok(count) if count > 0 => break Some(count),
+            _ => write(
+                &mut writer,
+                WriteArgs::Str("Please enter a positive integer.\n"),
+            )?,
+        }
+    };
+
+    Ok(Difficulty::Custom(Settings {
+        min,
+        max,
+        max_guesses,
+    }))
+}
+
+fn prompt_number(
+    question: &str,
+    mut writer: impl Write,
+    mut reader: impl BufRead,
+) -> Result<usize> {
+    loop {
+        write(&mut writer, WriteArgs::Str(question))?;
+        match prompt(&mut writer, &mut reader)?.parse() {
+            Ok(number) => return Ok(number),
+            Err(_) => write(
+                &mut writer,
+                WriteArgs::Str("Please enter a non-negative integer.\n"),
+            )?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::test_utils::setup_io_with_many_inputs;
+
+    use super::*;
+
+    #[test]
+    fn presets_get_harder() {
+        let sizes: Vec<usize> = Difficulty::PRESETS
+            .iter()
+            .map(|difficulty| difficulty.settings().max - difficulty.settings().min)
+            .collect();
+
+        assert!(sizes.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn presets_get_bigger_multipliers() {
+        let multipliers: Vec<f64> = Difficulty::PRESETS
+            .iter()
+            .map(|difficulty| difficulty.settings().multiplier())
+            .collect();
+
+        assert_eq!(multipliers, vec![1.0, 1.7, 2.0]);
+    }
+
+    #[test]
+    fn describe_includes_range_and_limit() {
+        assert_eq!(Difficulty::Normal.describe(), "normal: [0,100], 10 guesses");
+        assert_eq!(
+            Difficulty::Easy.describe(),
+            "easy: [0,10], unlimited guesses"
+        );
+    }
+
+    #[test]
+    fn prompt_custom_reads_range_and_limit() {
+        let inputs = ["5", "50", "6"];
+        let (writer, reader) = setup_io_with_many_inputs(&inputs);
+
+        assert_eq!(
+            prompt_custom(writer, reader).unwrap().settings(),
+            Settings {
+                min: 5,
+                max: 50,
+                max_guesses: Some(6),
+            }
+        )
+    }
+
+    #[test]
+    fn prompt_custom_allows_unlimited_guesses() {
+        let inputs = ["5", "50", ""];
+        let (writer, reader) = setup_io_with_many_inputs(&inputs);
+
+        assert_eq!(
+            prompt_custom(writer, reader)
+                .unwrap()
+                .settings()
+                .max_guesses,
+            None
+        )
+    }
+
+    #[test]
+    fn prompt_custom_returns_err_if_input_runs_out() {
+        let inputs = ["5"];
+        let (writer, reader) = setup_io_with_many_inputs(&inputs);
+
+        assert!(prompt_custom(writer, reader).is_err())
+    }
+
+    #[test]
+    fn prompt_custom_asks_again_after_invalid_answers() {
+        let inputs = ["low", "5", "4", "50", "0", "6"];
+        let (writer, reader) = setup_io_with_many_inputs(&inputs);
+
+        assert_eq!(
+            prompt_custom(writer, reader).unwrap().settings(),
+            Settings {
+                min: 5,
+                max: 50,
+                max_guesses: Some(6),
+            }
+        )
+    }
+}
@@ -0,0 +1,355 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{difficulty::Settings, feedback::Feedback, game::GameError, guesser::Guesser};
+
+/// Version of the engine protocol, sent to an engine when it starts.
+pub const VERSION: u32 = 1;
+/// Time an engine may take over each guess when `--timeout` isn't given.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+/// Time an engine has to start up & answer the handshake, which is longer
+/// than a guess may take so interpreters have time to load.
+pub const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time an engine has to exit once asked to, before it's killed.
+const EXIT_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Why an engine lost a game without finding the secret.
+#[derive(Debug)]
+pub enum Foul {
+    /// Took longer than the time allowed to answer
+    Timeout(Duration),
+    /// Answered with something the rules don't allow
+    Illegal(String),
+    /// Exited, or closed its output
+    Exited,
+    /// Starting or talking to the engine failed
+    Io(io::Error),
+}
+
+impl fmt::Display for Foul {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Foul::Timeout(timeout) => write!(f, "took longer than {:?} to answer", timeout),
+            Foul::Illegal(reason) => write!(f, "made an illegal move: {}", reason),
+            Foul::Exited => write!(f, "exited"),
+            Foul::Io(err) => write!(f, "couldn't be talked to: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for Foul {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            ErrorKind::BrokenPipe => Foul::Exited,
+            _ => Foul::Io(err),
+        }
+    }
+}
+
+/// A Guesser run as a separate program, which can be written in any
+/// language. The referee talks to it over its stdin & stdout, one message
+/// per line, much like a chess engine speaking UCI.
+///
+/// The referee sends:
+/// - `guessing_game 1` once the engine starts, giving the protocol VERSION.
+///   The engine answers `ready`, optionally after `name <name>`.
+/// - `new_game <min> <max> <max guesses or ->` before each round, whose
+///   secret is in the inclusive range [min, max].
+/// - `go` when it's time to guess. The engine answers `guess <n>` within the
+///   timeout.
+/// - `feedback <guess> <too_low|too_high|correct> <hot|warm|cold or ->`
+///   after each guess.
+/// - `quit` when it's done with the engine, which should then exit.
+///
+/// Engines may send `info <anything>` lines at any time, e.g. for
+/// debugging, which are ignored. Anything else, a guess outside the round's
+/// range or a guess made before in the round is an illegal move, & the
+/// engine forfeits the round, as it does if it's too slow or exits.
+pub struct Engine {
+    name: String,
+    timeout: Duration,
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines the engine writes, read on a separate thread so reads can time
+    /// out
+    lines: Receiver<io::Result<String>>,
+    settings: Settings,
+    guessed: HashSet<usize>,
+}
+
+impl Engine {
+    /// Start the engine run by the given command line, split on whitespace,
+    /// e.g. `"python3 my_engine.py"`, & shake hands with it. Each guess may
+    /// take up to `timeout`. The engine is named by the command unless it
+    /// gives a name of its own.
+    pub fn spawn(command: &str, timeout: Duration) -> Result<Self, Foul> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| Foul::Io(io::Error::new(ErrorKind::InvalidInput, "empty command")))?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = Engine {
+            name: command.to_string(),
+            timeout,
+            child,
+            stdin,
+            lines,
+            settings: Settings::default(),
+            guessed: HashSet::new(),
+        };
+
+        engine.send(&format!("guessing_game {}", VERSION))?;
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        loop {
+            let line = engine.receive(deadline, STARTUP_TIMEOUT)?;
+            match line.split_once(' ') {
+                _ if line == "ready" => return Ok(engine),
+                Some(("name", name)) => engine.name = name.trim().to_string(),
+                _ => {
+                    return Err(Foul::Illegal(format!(
+                        "expected 'ready' or 'name', got '{}'",
+                        line
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Name the engine gave, or the command it was started with.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Tell the engine a new round is starting with the given Settings.
+    pub fn new_game(&mut self, settings: Settings) -> Result<(), Foul> {
+        self.settings = settings;
+        self.guessed.clear();
+        let limit = match settings.max_guesses {
+            Some(limit) => limit.to_string(),
+            None => String::from("-"),
+        };
+
+        self.send(&format!(
+            "new_game {} {} {}",
+            settings.min, settings.max, limit
+        ))
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), Foul> {
+        writeln!(self.stdin, "{}", line)?;
+        self.stdin.flush()?;
+        Ok(())
+    }
+
+    /// The next line the engine writes other than `info`, waiting until the
+    /// deadline at most.
+    fn receive(&mut self, deadline: Instant, timeout: Duration) -> Result<String, Foul> {
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.lines.recv_timeout(left) {
+                Ok(Ok(line)) => {
+                    let line = line.trim();
+                    if line.is_empty() || line == "info" || line.starts_with("info ") {
+                        continue;
+                    }
+                    return Ok(line.to_string());
+                }
+                Ok(Err(err)) => return Err(err.into()),
+                Err(RecvTimeoutError::Timeout) => return Err(Foul::Timeout(timeout)),
+                Err(RecvTimeoutError::Disconnected) => return Err(Foul::Exited),
+            }
+        }
+    }
+
+    /// Ask the engine for its next guess & check it's legal.
+    fn next_guess(&mut self) -> Result<usize, Foul> {
+        self.send("go")?;
+        let line = self.receive(Instant::now() + self.timeout, self.timeout)?;
+        let guess = match line.split_once(' ') {
+            Some(("guess", guess)) => guess
+                .trim()
+                .parse()
+                .map_err(|_| Foul::Illegal(format!("'{}' isn't a number", guess.trim())))?,
+            _ => {
+                return Err(Foul::Illegal(format!(
+                    "expected 'guess <n>', got '{}'",
+                    line
+                )))
+            }
+        };
+
+        let Settings { min, max, .. } = self.settings;
+        if guess < min || guess > max {
+            return Err(Foul::Illegal(format!(
+                "{} is outside the range [{}, {}]",
+                guess, min, max
+            )));
+        }
+        if !self.guessed.insert(guess) {
+            return Err(Foul::Illegal(format!("{} was already guessed", guess)));
+        }
+
+        Ok(guess)
+    }
+}
+
+impl Guesser for Engine {
+    fn guess(&mut self) -> Result<usize, GameError> {
+        self.next_guess().map_err(GameError::Forfeit)
+    }
+
+    fn feedback(&mut self, feedback: Feedback) -> Result<(), GameError> {
+        let distance = feedback.distance.map_or("-", |d| d.name());
+        self.send(&format!(
+            "feedback {} {} {}",
+            feedback.guess,
+            feedback.direction.name(),
+            distance
+        ))
+        .map_err(GameError::Forfeit)
+    }
+}
+
+impl Drop for Engine {
+    /// Ask the engine to quit, killing it if it doesn't exit promptly.
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let deadline = Instant::now() + EXIT_TIMEOUT;
+        while Instant::now() < deadline {
+            match self.child.try_wait() {
+                Ok(None) => thread::sleep(Duration::from_millis(5)),
+                _ => return,
+            }
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use crate::{
+        constants::{MAX_SECRET, MIN_SECRET},
+        game::{play_round, GameState},
+    };
+
+    use super::*;
+
+    /// A binary search engine written in sh, as an engine from another
+    /// language would be. `go` runs `on_go` first, to misbehave with.
+    fn engine_script(name: &str, on_go: &str) -> PathBuf {
+        let path = env::temp_dir()
+            .join(format!("guessing_game_engine_test_{}", std::process::id()))
+            .join(format!("{}.sh", name));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            format!(
+                r#"read hello
+echo "info starting up"
+echo "name sh-{}"
+echo ready
+while read cmd a b c; do
+  case $cmd in
+    new_game) lo=$a; hi=$b ;;
+    go) {}; echo "guess $(( (lo + hi) / 2 ))" ;;
+    feedback) case $b in too_low) lo=$((a + 1)) ;; too_high) hi=$((a - 1)) ;; esac ;;
+    quit) exit 0 ;;
+  esac
+done
+"#,
+                name, on_go
+            ),
+        )
+        .unwrap();
+        path
+    }
+
+    fn spawn(name: &str, on_go: &str) -> Engine {
+        let script = engine_script(name, on_go);
+        let command = format!("sh {}", script.display());
+        Engine::spawn(&command, Duration::from_millis(500)).unwrap()
+    }
+
+    fn play(engine: &mut Engine, secret: usize) -> Result<usize, GameError> {
+        let settings = Settings {
+            min: MIN_SECRET,
+            max: MAX_SECRET,
+            max_guesses: None,
+        };
+        engine.new_game(settings).map_err(GameError::Forfeit)?;
+        play_round(&mut GameState::new(secret, settings), engine)
+    }
+
+    #[test]
+    fn engine_plays_rounds_like_the_built_in_binary_search() {
+        let mut engine = spawn("binary", ":");
+
+        assert_eq!(engine.name(), "sh-binary");
+        for secret in [0, 37, 100] {
+            let mut binary = crate::players::BinaryPlayer::new(MIN_SECRET, MAX_SECRET);
+            let expected = crate::game::play_with(secret, &mut binary).unwrap();
+            assert_eq!(play(&mut engine, secret).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn slow_engines_forfeit() {
+        let mut engine = spawn("slow", "sleep 2");
+
+        assert!(matches!(
+            play(&mut engine, 37),
+            Err(GameError::Forfeit(Foul::Timeout(_)))
+        ));
+    }
+
+    #[test]
+    fn illegal_moves_forfeit() {
+        let mut repeats = spawn("repeats", "lo=0; hi=0");
+        let mut out_of_range = spawn("out_of_range", "lo=500; hi=500");
+        let mut garbled = spawn("garbled", "echo 'guess fifty'");
+        let mut quitter = spawn("quitter", "exit 0");
+
+        assert!(matches!(
+            play(&mut repeats, 37),
+            Err(GameError::Forfeit(Foul::Illegal(reason))) if reason.contains("already guessed")
+        ));
+        assert!(matches!(
+            play(&mut out_of_range, 37),
+            Err(GameError::Forfeit(Foul::Illegal(reason))) if reason.contains("outside the range")
+        ));
+        assert!(matches!(
+            play(&mut garbled, 37),
+            Err(GameError::Forfeit(Foul::Illegal(reason))) if reason.contains("isn't a number")
+        ));
+        assert!(matches!(
+            play(&mut quitter, 37),
+            Err(GameError::Forfeit(Foul::Exited))
+        ));
+    }
+}
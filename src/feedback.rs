@@ -0,0 +1,182 @@
+use std::cmp::Ordering;
+
+/// Which way a guess was off from the secret, if at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    TooLow,
+    TooHigh,
+    Correct,
+}
+
+impl Direction {
+    /// Compare a guess to the secret.
+    pub fn of(guess: usize, secret: usize) -> Self {
+        match guess.cmp(&secret) {
+            Ordering::Equal => Direction::Correct,
+            Ordering::Less => Direction::TooLow,
+            Ordering::Greater => Direction::TooHigh,
+        }
+    }
+
+    /// Every direction a guess can be off in, or not.
+    pub const ALL: [Direction; 3] = [Direction::TooLow, Direction::TooHigh, Direction::Correct];
+
+    /// Name used for the direction in recordings & by the machine protocols,
+    /// see crate::recording, crate::protocol & crate::engine.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::TooLow => "too_low",
+            Direction::TooHigh => "too_high",
+            Direction::Correct => "correct",
+        }
+    }
+
+    /// Find the direction with the given name.
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|direction| direction.name() == name)
+    }
+}
+
+/// How far a guess was from the secret, relative to the size of the range
+/// the secret was picked from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Distance {
+    /// Within 5% of the range
+    Hot,
+    /// Within 20% of the range
+    Warm,
+    Cold,
+}
+
+impl Distance {
+    /// Bucket the distance between a guess & the secret, picked from the
+    /// inclusive range [min, max].
+    pub fn between(guess: usize, secret: usize, min: usize, max: usize) -> Self {
+        let width = (max - min).max(1) as f64;
+        let off = guess.abs_diff(secret) as f64 / width;
+
+        if off <= 0.05 {
+            Distance::Hot
+        } else if off <= 0.2 {
+            Distance::Warm
+        } else {
+            Distance::Cold
+        }
+    }
+
+    /// Every distance a wrong guess can be bucketed into.
+    pub const ALL: [Distance; 3] = [Distance::Hot, Distance::Warm, Distance::Cold];
+
+    /// Name used for the distance in recordings & by the machine protocols,
+    /// see crate::recording, crate::protocol & crate::engine.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Distance::Hot => "hot",
+            Distance::Warm => "warm",
+            Distance::Cold => "cold",
+        }
+    }
+
+    /// Find the distance with the given name.
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|distance| distance.name() == name)
+    }
+}
+
+/// The result of comparing a guess to the secret number, given back to a
+/// Guesser after each guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Feedback {
+    pub guess: usize,
+    pub direction: Direction,
+    /// How far off a wrong guess was, if known
+    pub distance: Option<Distance>,
+}
+
+impl Feedback {
+    /// Feedback for a guess with no distance given.
+    pub fn new(guess: usize, direction: Direction) -> Self {
+        Feedback {
+            guess,
+            direction,
+            distance: None,
+        }
+    }
+
+    /// Whether the guess was the secret.
+    pub fn is_correct(&self) -> bool {
+        self.direction == Direction::Correct
+    }
+}
+
+/// Render Feedback as the text shown to a human player, e.g.
+/// `"42 is too low! (close)"`.
+pub fn render(feedback: &Feedback) -> String {
+    let direction = match feedback.direction {
+        Direction::TooLow => "is too low!",
+        Direction::TooHigh => "is too high!",
+        Direction::Correct => return String::from("Correct!"),
+    };
+
+    match feedback.distance {
+        Some(distance) => format!(
+            "{} {} ({})",
+            feedback.guess,
+            direction,
+            render_distance(distance)
+        ),
+        None => format!("{} {}", feedback.guess, direction),
+    }
+}
+
+fn render_distance(distance: Distance) -> &'static str {
+    match distance {
+        Distance::Hot => "very close",
+        Distance::Warm => "close",
+        Distance::Cold => "far off",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_is_relative_to_range() {
+        assert_eq!(Distance::between(48, 50, 0, 100), Distance::Hot);
+        assert_eq!(Distance::between(35, 50, 0, 100), Distance::Warm);
+        assert_eq!(Distance::between(10, 50, 0, 100), Distance::Cold);
+        assert_eq!(Distance::between(10, 50, 0, 1000), Distance::Hot);
+    }
+
+    #[test]
+    fn names_parse_back() {
+        for direction in Direction::ALL {
+            assert_eq!(Direction::parse(direction.name()), Some(direction));
+        }
+        for distance in Distance::ALL {
+            assert_eq!(Distance::parse(distance.name()), Some(distance));
+        }
+        assert_eq!(Direction::parse("low"), None);
+    }
+
+    #[test]
+    fn render_includes_guess_direction_and_distance() {
+        let feedback = Feedback {
+            guess: 42,
+            direction: Direction::TooLow,
+            distance: Some(Distance::Warm),
+        };
+
+        assert_eq!(render(&feedback), "42 is too low! (close)");
+        assert_eq!(
+            render(&Feedback::new(7, Direction::TooHigh)),
+            "7 is too high!"
+        );
+        assert_eq!(render(&Feedback::new(7, Direction::Correct)), "Correct!");
+    }
+}
@@ -1,113 +1,533 @@
 use std::{
-    cmp::Ordering,
-    io::{BufRead, Write},
-    num::ParseIntError,
+    io::{self, BufRead, Write},
+    time::{Duration, Instant},
 };
 
-use crate::io::{prompt, write, WriteArgs};
+use crate::{
+    commands::{self, CommandAction},
+    difficulty::Settings,
+    engine::Foul,
+    feedback::{render, Direction, Distance, Feedback},
+    guesser::Guesser,
+    hints::{Hint, HintKind, TakenHint},
+    io::{is_interrupt, prompt, write, WriteArgs},
+};
 
-/// Types of Errors that can be returned at the end of a game. Quit is used to
-/// indicate the user requested to quit the game, Unknown shouldn't happen,
-/// but exists to cover unexpected behavior.
+/// Types of Errors that can be returned at the end of a game.
 #[derive(Debug)]
 pub enum GameError {
+    /// The user asked to quit the game
     Quit,
+    /// The user asked to save the game to finish later
+    Saved,
+    /// The guesser used every guess allowed without finding the secret
+    OutOfGuesses,
+    /// A human answering guesses ruled out every number
+    Contradiction,
+    /// An engine guessing from another process broke the engine protocol
+    Forfeit(Foul),
+    /// Reading or writing the game's streams failed, including running out
+    /// of input
+    Io(io::Error),
+    /// Shouldn't happen, but covers unexpected behavior
     Unknown,
 }
 
+impl From<io::Error> for GameError {
+    fn from(err: io::Error) -> Self {
+        GameError::Io(err)
+    }
+}
+
+/// Why a guess can't be counted, see GameState::check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    /// Outside the round's range
+    OutOfRange,
+    /// Already guessed in the round
+    Repeated,
+}
+
+/// The rules of a round, free of any I/O: knows the secret & Settings,
+/// evaluates guesses against them & keeps track of every guess made so far &
+/// the interval the secret is known to be in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameState {
+    secret: usize,
+    settings: Settings,
+    /// Whether the secret is known to be in the Settings' range, so the
+    /// Distance of a guess can be measured against it
+    bounded: bool,
+    history: Vec<Feedback>,
+    hints: Vec<TakenHint>,
+    low: usize,
+    high: usize,
+}
+
+impl GameState {
+    /// Start a round with the given secret & Settings, before any guesses.
+    pub fn new(secret: usize, settings: Settings) -> Self {
+        GameState {
+            secret,
+            settings,
+            bounded: true,
+            history: Vec::new(),
+            hints: Vec::new(),
+            low: settings.min,
+            high: settings.max,
+        }
+    }
+
+    /// Start a round with the given secret & no known range, allowing at
+    /// most `max_guesses` guesses if given. Feedback in an unbounded round
+    /// has no Distance, as there's no range to measure it against.
+    pub fn unbounded(secret: usize, max_guesses: Option<usize>) -> Self {
+        let settings = Settings {
+            min: usize::MIN,
+            max: usize::MAX,
+            max_guesses,
+        };
+
+        GameState {
+            bounded: false,
+            ..Self::new(secret, settings)
+        }
+    }
+
+    /// Check whether a guess may be counted: it has to be in the round's
+    /// range & not guessed before. Guesses already ruled out by earlier
+    /// Feedback still count.
+    pub fn check(&self, guess: usize) -> Result<(), Rejection> {
+        if guess < self.settings.min || guess > self.settings.max {
+            return Err(Rejection::OutOfRange);
+        }
+        if self.history.iter().any(|f| f.guess == guess) {
+            return Err(Rejection::Repeated);
+        }
+
+        Ok(())
+    }
+
+    /// Evaluate a guess, recording it & narrowing the known interval, & return
+    /// the Feedback for it. Guesses aren't checked, see `check`, nor is
+    /// whether the round is over, that's up to the caller.
+    pub fn guess(&mut self, guess: usize) -> Feedback {
+        let feedback = self.evaluate(guess);
+
+        match feedback.direction {
+            Direction::TooLow => self.low = self.low.max(guess + 1),
+            Direction::TooHigh => self.high = self.high.min(guess - 1),
+            Direction::Correct => (self.low, self.high) = (guess, guess),
+        }
+        self.history.push(feedback);
+
+        feedback
+    }
+
+    /// Number of guesses made so far.
+    pub fn guesses(&self) -> usize {
+        self.history.len()
+    }
+
+    /// The Feedback for every guess made so far, oldest first.
+    pub fn history(&self) -> &[Feedback] {
+        &self.history
+    }
+
+    /// The inclusive interval [low, high] the secret must be in, given the
+    /// Feedback so far.
+    pub fn interval(&self) -> (usize, usize) {
+        (self.low, self.high)
+    }
+
+    /// Work out a hint of the given kind from the secret & what's known about
+    /// it so far, recording that it was taken.
+    pub fn hint(&mut self, kind: HintKind) -> Hint {
+        let taken = self.hints.iter().filter(|h| h.kind == kind).count();
+        let hint = Hint::new(kind, self.secret, self.interval(), taken);
+        self.hints.push(TakenHint {
+            kind,
+            hint,
+            after: self.guesses(),
+        });
+
+        hint
+    }
+
+    /// Every hint taken so far, oldest first.
+    pub fn hints(&self) -> &[TakenHint] {
+        &self.hints
+    }
+
+    /// Total points the hints taken so far cost.
+    pub fn hint_penalty(&self) -> u32 {
+        self.hints.iter().map(|h| h.kind.cost()).sum()
+    }
+
+    /// Number of guesses left before the round is lost, if limited.
+    pub fn guesses_left(&self) -> Option<usize> {
+        self.settings
+            .max_guesses
+            .map(|max| max.saturating_sub(self.guesses()))
+    }
+
+    /// Whether the secret has been guessed.
+    pub fn is_won(&self) -> bool {
+        self.history.last().is_some_and(Feedback::is_correct)
+    }
+
+    /// Whether the round is over, either won or out of guesses.
+    pub fn is_over(&self) -> bool {
+        self.is_won() || self.guesses_left() == Some(0)
+    }
+
+    /// The secret being guessed.
+    pub fn secret(&self) -> usize {
+        self.secret
+    }
+
+    /// The Settings the round is played with.
+    pub fn settings(&self) -> Settings {
+        self.settings
+    }
+
+    /// Compare a guess to the secret & return Feedback saying if it is
+    /// correct, too high, or too low, & how far off it is if known.
+    fn evaluate(&self, guess: usize) -> Feedback {
+        let direction = Direction::of(guess, self.secret);
+        let distance = match direction {
+            Direction::Correct => None,
+            _ if !self.bounded => None,
+            _ => Some(Distance::between(
+                guess,
+                self.secret,
+                self.settings.min,
+                self.settings.max,
+            )),
+        };
+
+        Feedback {
+            guess,
+            direction,
+            distance,
+        }
+    }
+}
+
 /// Represents a game as an object that knows a secret number & exposes
 /// a `play` method that prompts the guesser to guess in a loop until the
 /// guess correctly.
 pub struct Game<W: Write, R: BufRead> {
     reader: R,
-    secret: usize,
+    state: GameState,
     writer: W,
+    /// Time spent playing the round so far
+    elapsed: Duration,
+    /// When the round was last picked up, while it's being played
+    resumed_at: Option<Instant>,
+    /// Time into the round each guess in the state's history was made
+    times: Vec<Duration>,
 }
 
 impl<W: Write, R: BufRead> Game<W, R> {
-    /// Create a new Game instance with the given secret number & io streams.
+    /// Create a new Game instance with the given secret number & io streams,
+    /// using the default Settings.
     pub fn new(secret: usize, writer: W, reader: R) -> Self {
+        Self::with_settings(secret, Settings::default(), writer, reader)
+    }
+
+    /// Create a new Game instance with the given secret number, Settings, &
+    /// io streams.
+    pub fn with_settings(secret: usize, settings: Settings, writer: W, reader: R) -> Self {
+        Self::from_state(
+            GameState::new(secret, settings),
+            Vec::new(),
+            Duration::ZERO,
+            writer,
+            reader,
+        )
+    }
+
+    /// Create a Game instance that picks up a round from the given state,
+    /// after `elapsed` has already been spent playing it, e.g. a round
+    /// restored from a crate::save::SavedGame. `times` gives the time into
+    /// the round each guess already in the state was made.
+    pub fn from_state(
+        state: GameState,
+        times: Vec<Duration>,
+        elapsed: Duration,
+        writer: W,
+        reader: R,
+    ) -> Self {
         Game {
-            secret,
+            times,
+            state,
             writer,
             reader,
+            elapsed,
+            resumed_at: None,
         }
     }
 
-    /// Main function for starting a game round. Gets a secret number, then starts a
-    /// loop prompting the Guesser to guess in each iteration. Continues looping
-    /// until the Guesser submits a correct guess.  Returns Ok when the loop ends.
-    /// Exits loop early & returns Err if user enters "quit" instead of a guess.
+    /// Main function for starting a game round with a human guessing from
+    /// this Game's io streams. Prompts for guesses in a loop, telling the
+    /// human how each compares to the secret & where the secret must be
+    /// given their guesses so far, & running any SlashCommand they enter
+    /// instead, e.g. `/history`. Repeated guesses & guesses outside the
+    /// round's range aren't counted, & guesses already ruled out are counted
+    /// with a warning. Returns Ok when the
+    /// human guesses correctly, or Err if they enter "quit" or give up
+    /// instead of a guess or run out of guesses. Entering `/save` returns
+    /// Err(Saved), leaving the round to be saved & resumed later. Pressing
+    /// Ctrl-C asks the human whether to keep playing, save the round, quit
+    /// it or exit the game.
     pub fn play(&mut self) -> Result<(), GameError> {
-        // create variable to store game result
-        let mut res: Result<(), GameError> = Err(GameError::Unknown);
-        // set up loop
-        let mut keep_guessing = true;
+        self.state = GameState::new(self.state.secret(), self.state.settings());
+        self.elapsed = Duration::ZERO;
+        self.times.clear();
+        self.resume()
+    }
 
-        while keep_guessing {
+    /// Carry on playing the round from its current state, as `play` does,
+    /// adding the time spent to the time already spent on it.
+    pub fn resume(&mut self) -> Result<(), GameError> {
+        self.resumed_at = Some(Instant::now());
+        let result = self.run();
+        self.elapsed = self.elapsed();
+        self.resumed_at = None;
+        result
+    }
+
+    /// Time spent playing the round so far.
+    pub fn elapsed(&self) -> Duration {
+        match self.resumed_at {
+            Some(start) => self.elapsed + start.elapsed(),
+            None => self.elapsed,
+        }
+    }
+
+    /// Time into the round each guess was made, matching the state's
+    /// history.
+    pub fn times(&self) -> &[Duration] {
+        &self.times
+    }
+
+    fn run(&mut self) -> Result<(), GameError> {
+        loop {
             // prompt for guess
-            write(&mut self.writer, WriteArgs::Str("Guess a number...\n"));
-            let guess_value = prompt(&mut self.writer, &mut self.reader);
-            let guess_parsed: Result<usize, ParseIntError> = guess_value.parse();
-            match guess_parsed {
-                // if guess parses to int evaluate it
-                Ok(guess) => {
-                    let evaluated = self.evaluate(guess);
-
-                    if let Err(value) = evaluated {
+            write(&mut self.writer, WriteArgs::Str("Guess a number...\n"))?;
+            let input = match prompt(&mut self.writer, &mut self.reader) {
+                Err(err) if is_interrupt(&err) => {
+                    self.confirm_interrupt(err)?;
+                    continue;
+                }
+                input => input?,
+            };
+
+            if input == "quit" {
+                write(&mut self.writer, WriteArgs::Str("Quitting...\n"))?;
+                return Err(GameError::Quit);
+            }
+            if let Some(action) = commands::run(&input, &mut self.state, &mut self.writer) {
+                match action? {
+                    CommandAction::Continue => continue,
+                    CommandAction::Save => {
+                        write(&mut self.writer, WriteArgs::Str("Saving...\n"))?;
+                        return Err(GameError::Saved);
+                    }
+                    CommandAction::GiveUp => {
                         write(
                             &mut self.writer,
-                            WriteArgs::Fmt(format_args!("{}\n\n", value)),
-                        )
-                    } else {
-                        keep_guessing = false;
-                        res = Ok(());
-                        write(&mut self.writer, WriteArgs::Str("Correct! "));
+                            WriteArgs::Fmt(format_args!(
+                                "Giving up... the secret was {}.\n",
+                                self.state.secret()
+                            )),
+                        )?;
+                        return Err(GameError::Quit);
                     }
                 }
-                // return error if guess is "quit"
+            }
+
+            let guess = match input.parse() {
+                Ok(guess) => guess,
                 Err(_) => {
-                    if let "quit" = guess_value.as_str() {
-                        keep_guessing = false;
-                        write(&mut self.writer, WriteArgs::Str("Quitting...\n"));
-                        res = Err(GameError::Quit);
-                    } else {
-                        write(
-                            &mut self.writer,
-                            WriteArgs::Str("Invalid input, please guess an integer belonging to [0,100] or enter 'quit' to quit playing.\n")
-                        );
-                    }
+                    let settings = self.state.settings();
+                    write(
+                        &mut self.writer,
+                        WriteArgs::Fmt(format_args!(
+                            "Invalid input, please guess an integer belonging to [{},{}], enter /help to see the commands or 'quit' to quit playing.\n",
+                            settings.min, settings.max
+                        )),
+                    )?;
+                    continue;
                 }
+            };
+
+            let settings = self.state.settings();
+            match self.state.check(guess) {
+                Ok(()) => (),
+                Err(Rejection::OutOfRange) => {
+                    write(
+                        &mut self.writer,
+                        WriteArgs::Fmt(format_args!(
+                            "Please guess a number in [{}, {}], that one's free.\n\n",
+                            settings.min, settings.max
+                        )),
+                    )?;
+                    continue;
+                }
+                Err(Rejection::Repeated) => {
+                    write(
+                        &mut self.writer,
+                        WriteArgs::Fmt(format_args!(
+                            "You already guessed {}, that one's free.\n\n",
+                            guess
+                        )),
+                    )?;
+                    continue;
+                }
+            }
+            let (low, high) = self.state.interval();
+            if guess < low || guess > high {
+                write(
+                    &mut self.writer,
+                    WriteArgs::Fmt(format_args!(
+                        "{} was already ruled out, the secret is in [{}, {}].\n",
+                        guess, low, high
+                    )),
+                )?;
+            }
+
+            let feedback = self.state.guess(guess);
+            self.times.push(self.elapsed());
+            if feedback.is_correct() {
+                write(&mut self.writer, WriteArgs::Str("Correct! "))?;
+                return Ok(());
+            }
+            write(
+                &mut self.writer,
+                WriteArgs::Fmt(format_args!("{}\n", render(&feedback))),
+            )?;
+            if self.state.is_over() {
+                write(&mut self.writer, WriteArgs::Str("\n"))?;
+                return Err(GameError::OutOfGuesses);
             }
+            self.write_status()?;
         }
+    }
+
+    /// Ask the human what to do after they press Ctrl-C mid-round. Returns
+    /// Ok to keep playing, Err(Saved) to save the round, Err(Quit) to quit
+    /// it, or Err(Io) with the interrupt error to exit the game, which
+    /// pressing Ctrl-C again also does.
+    fn confirm_interrupt(&mut self, interrupt: io::Error) -> Result<(), GameError> {
+        write(
+            &mut self.writer,
+            WriteArgs::Str("\nKeep playing (k), save this round for later (s), quit it (q) or exit the game (x)?\n"),
+        )?;
+
+        loop {
+            match prompt(&mut self.writer, &mut self.reader) {
+                Ok(answer) => match answer.as_str() {
+                    "k" | "" => return Ok(()),
+                    "s" => {
+                        write(&mut self.writer, WriteArgs::Str("Saving...\n"))?;
+                        return Err(GameError::Saved);
+                    }
+                    "q" => {
+                        write(&mut self.writer, WriteArgs::Str("Quitting...\n"))?;
+                        return Err(GameError::Quit);
+                    }
+                    "x" => return Err(GameError::Io(interrupt)),
+                    _ => write(
+                        &mut self.writer,
+                        WriteArgs::Str("Please enter k, s, q or x.\n"),
+                    )?,
+                },
+                // including a second Ctrl-C
+                Err(err) => return Err(GameError::Io(err)),
+            }
+        }
+    }
+
+    /// Tell the human where the secret must be & how many guesses they have
+    /// left, if limited.
+    fn write_status(&mut self) -> Result<(), GameError> {
+        let (low, high) = self.state.interval();
+        match self.state.guesses_left() {
+            Some(left) => write(
+                &mut self.writer,
+                WriteArgs::Fmt(format_args!(
+                    "The secret is in [{}, {}], {} guesses left.\n\n",
+                    low, high, left
+                )),
+            )?,
+            None => write(
+                &mut self.writer,
+                WriteArgs::Fmt(format_args!("The secret is in [{}, {}].\n\n", low, high)),
+            )?,
+        }
+
+        Ok(())
+    }
+
+    /// Number of guesses made in the last call to `play`, however it ended.
+    pub fn guesses(&self) -> usize {
+        self.state.guesses()
+    }
 
-        res
+    /// The state the last call to `play` left the round in.
+    pub fn state(&self) -> &GameState {
+        &self.state
     }
+}
+
+/// Play a round against the given secret with any Guesser & no limit on the
+/// number of guesses. See `play_limited`.
+pub fn play_with(secret: usize, guesser: &mut impl Guesser) -> Result<usize, GameError> {
+    play_limited(secret, None, guesser)
+}
+
+/// Play a round against the given secret with any Guesser, allowing at most
+/// `max_guesses` guesses if given. See `play_round`.
+pub fn play_limited(
+    secret: usize,
+    max_guesses: Option<usize>,
+    guesser: &mut impl Guesser,
+) -> Result<usize, GameError> {
+    play_round(&mut GameState::unbounded(secret, max_guesses), guesser)
+}
 
-    /// Compare two numbers and return Ok if equal, otherwise Err with value of too
-    /// high or too low if not equal.
-    fn evaluate(&self, actual: usize) -> Result<(), String> {
-        match actual.cmp(&self.secret) {
-            Ordering::Equal => Ok(()),
-            Ordering::Less => Err(format!("{} is too low!", actual)),
-            Ordering::Greater => Err(format!("{} is too high!", actual)),
+/// Play out a round from the given state with any Guesser. Loops asking the
+/// Guesser for a guess & giving it Feedback on that guess until the Guesser
+/// guesses correctly, then returns Ok with the number of guesses it took.
+/// Exits loop early & returns Err if the Guesser gives up or uses all of the
+/// round's guesses without guessing correctly.
+pub fn play_round(state: &mut GameState, guesser: &mut impl Guesser) -> Result<usize, GameError> {
+    loop {
+        let guess = guesser.guess()?;
+        let feedback = state.guess(guess);
+        guesser.feedback(feedback)?;
+
+        if state.is_won() {
+            return Ok(state.guesses());
+        }
+        if state.is_over() {
+            return Err(GameError::OutOfGuesses);
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::io::test_utils::{
-        setup_io, setup_io_with_input, setup_io_with_many_inputs, TestReader, TestWriter,
+    use crate::{
+        constants::MAX_SECRET,
+        guesser::HumanGuesser,
+        io::test_utils::{setup_io, setup_io_with_input, setup_io_with_many_inputs, INTERRUPT},
     };
 
     use super::*;
 
-    pub fn setup_game_with_secret(secret: usize) -> Game<TestWriter, TestReader> {
-        let (writer, reader) = setup_io();
-        Game::new(secret, writer, reader)
-    }
-
     #[test]
     fn takes_secret_and_io_read_and_write_streams_on_init() -> Result<(), String> {
         let (mut writer, reader) = setup_io();
@@ -153,6 +573,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn play_game_counts_guesses_even_if_user_quits() {
+        let guesses = ["0", "2", "quit"];
+        let (writer, reader) = setup_io_with_many_inputs(&guesses);
+        let mut game = Game::new(1, writer, reader);
+        let _ = game.play();
+
+        assert_eq!(game.guesses(), 2)
+    }
+
+    #[test]
+    fn play_game_returns_io_error_if_input_runs_out() {
+        let guesses = ["0", "2"];
+        let (writer, reader) = setup_io_with_many_inputs(&guesses);
+        let mut game = Game::new(1, writer, reader);
+
+        assert!(matches!(game.play(), Err(GameError::Io(_))))
+    }
+
+    #[test]
+    fn save_command_leaves_the_round_to_resume() {
+        let (writer, reader) = setup_io_with_many_inputs(&["0", "/save"]);
+        let mut game = Game::new(1, writer, reader);
+        assert!(matches!(game.play(), Err(GameError::Saved)));
+
+        let (writer, reader) = setup_io_with_many_inputs(&["1"]);
+        let (state, times) = (game.state().clone(), game.times().to_vec());
+        let mut game = Game::from_state(state, times, game.elapsed(), writer, reader);
+        assert!(game.resume().is_ok());
+        assert_eq!(game.guesses(), 2);
+        assert_eq!(game.times().len(), 2);
+    }
+
+    #[test]
+    fn ctrl_c_asks_whether_to_keep_playing_quit_or_exit() {
+        let (writer, reader) = setup_io_with_many_inputs(&["0", INTERRUPT, "k", "1"]);
+        let mut game = Game::new(1, writer, reader);
+        assert!(game.play().is_ok());
+        assert_eq!(game.guesses(), 2);
+
+        let (writer, reader) = setup_io_with_many_inputs(&[INTERRUPT, "q"]);
+        assert!(matches!(
+            Game::new(1, writer, reader).play(),
+            Err(GameError::Quit)
+        ));
+
+        for inputs in [[INTERRUPT, "x"], [INTERRUPT, INTERRUPT]] {
+            let (writer, reader) = setup_io_with_many_inputs(&inputs);
+            match Game::new(1, writer, reader).play() {
+                Err(GameError::Io(err)) => assert!(is_interrupt(&err)),
+                other => panic!("expected an interrupt, got {:?}", other),
+            }
+        }
+    }
+
     #[test]
     fn play_game_alerts_guesser_if_input_is_invalid() -> Result<(), String> {
         let guesses = ["not a valid input", "1"];
@@ -187,48 +662,256 @@ mod tests {
     }
 
     #[test]
-    fn evaluate_returns_ok_if_guess_is_correct() -> Result<(), String> {
-        let game = setup_game_with_secret(1);
-        game.evaluate(1)
+    fn play_with_returns_number_of_guesses_taken() -> Result<(), String> {
+        let guesses = ["0", "5", "1"];
+        let (writer, reader) = setup_io_with_many_inputs(&guesses);
+        let mut guesser = HumanGuesser::new(writer, reader);
+
+        match play_with(1, &mut guesser) {
+            Ok(3) => Ok(()),
+            other => Err(format!("Expected Ok(3), got {:?}", other)),
+        }
     }
 
     #[test]
-    fn evaluate_returns_err_if_guess_is_incorrect() -> Result<(), String> {
-        let game = setup_game_with_secret(2);
+    fn play_limited_returns_out_of_guesses_when_limit_is_used_up() {
+        let guesses = ["0", "5", "1"];
+        let (writer, reader) = setup_io_with_many_inputs(&guesses);
+        let mut guesser = HumanGuesser::new(writer, reader);
 
-        match game.evaluate(1) {
-            Err(_) => Ok(()),
-            _ => Err(String::from("This should have Errored")),
-        }
+        assert!(matches!(
+            play_limited(1, Some(2), &mut guesser),
+            Err(GameError::OutOfGuesses)
+        ))
     }
 
     #[test]
-    fn evaluate_specifies_if_guess_is_too_high() {
-        let game = setup_game_with_secret(10);
-        let reason = match game.evaluate(11) {
-            Err(reason) => reason,
-            _ => panic!("evaluate should be Err"),
+    fn play_limited_allows_correct_last_guess() {
+        let guesses = ["0", "5", "1"];
+        let (writer, reader) = setup_io_with_many_inputs(&guesses);
+        let mut guesser = HumanGuesser::new(writer, reader);
+
+        assert!(matches!(play_limited(1, Some(3), &mut guesser), Ok(3)))
+    }
+
+    #[test]
+    fn play_game_with_settings_reports_loss_after_max_guesses() {
+        let guesses = ["0", "2"];
+        let (writer, reader) = setup_io_with_many_inputs(&guesses);
+        let settings = Settings {
+            min: 0,
+            max: 10,
+            max_guesses: Some(2),
         };
+        let mut game = Game::with_settings(1, settings, writer, reader);
 
-        let expected = "too high";
-        assert!(
-            reason.contains(expected),
-            "{reason} should contain {expected}"
-        )
+        assert!(matches!(game.play(), Err(GameError::OutOfGuesses)))
     }
 
     #[test]
-    fn evaluate_specifies_if_guess_is_too_low() {
-        let game = setup_game_with_secret(10);
-        let reason = match game.evaluate(9) {
-            Err(reason) => reason,
-            _ => panic!("evaluate should be Err"),
+    fn play_game_mentions_range_when_input_is_invalid() {
+        let guesses = ["not a valid input", "1"];
+        let (mut writer, reader) = setup_io_with_many_inputs(&guesses);
+        let settings = Settings {
+            min: 0,
+            max: 10,
+            max_guesses: None,
         };
+        let mut game = Game::with_settings(1, settings, &mut writer, reader);
+        game.play().unwrap();
 
-        let expected = "too low";
-        assert!(
-            reason.contains(expected),
-            "{reason} should contain {expected}"
-        )
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains("[0,10]")));
+    }
+
+    #[test]
+    fn play_game_shows_interval_after_each_guess() {
+        let guesses = ["50", "20", "40"];
+        let (mut writer, reader) = setup_io_with_many_inputs(&guesses);
+        let mut game = Game::new(40, &mut writer, reader);
+        game.play().unwrap();
+
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line == "The secret is in [21, 49].\n\n"));
+    }
+
+    #[test]
+    fn play_game_lists_history_when_asked() {
+        let guesses = ["50", "20", "/history", "40"];
+        let (mut writer, reader) = setup_io_with_many_inputs(&guesses);
+        let mut game = Game::new(40, &mut writer, reader);
+        game.play().unwrap();
+
+        assert_eq!(game.guesses(), 3);
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains("2. 20 is too low!")));
+    }
+
+    #[test]
+    fn play_game_reveals_secret_if_user_gives_up() {
+        let guesses = ["50", "/giveup"];
+        let (mut writer, reader) = setup_io_with_many_inputs(&guesses);
+        let mut game = Game::new(40, &mut writer, reader);
+
+        assert!(matches!(game.play(), Err(GameError::Quit)));
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains("the secret was 40")));
+    }
+
+    #[test]
+    fn play_game_does_not_count_repeated_guesses() {
+        let guesses = ["50", "50", "40"];
+        let (mut writer, reader) = setup_io_with_many_inputs(&guesses);
+        let mut game = Game::new(40, &mut writer, reader);
+        game.play().unwrap();
+
+        assert_eq!(game.guesses(), 2);
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains("already guessed 50")));
+    }
+
+    #[test]
+    fn play_game_works_at_the_top_of_usize() {
+        let settings = Settings {
+            min: 0,
+            max: usize::MAX,
+            max_guesses: None,
+        };
+        let top = usize::MAX.to_string();
+        let inputs = ["/stats", "/hint near", "/hint next", "0", "/stats", &top];
+        let (mut writer, reader) = setup_io_with_many_inputs(&inputs);
+        let mut game = Game::with_settings(usize::MAX, settings, &mut writer, reader);
+
+        assert!(game.play().is_ok());
+        assert_eq!(game.guesses(), 2);
+    }
+
+    #[test]
+    fn play_game_does_not_count_guesses_outside_the_range() {
+        let guesses = ["1000", "40"];
+        let (mut writer, reader) = setup_io_with_many_inputs(&guesses);
+        let mut game = Game::new(40, &mut writer, reader);
+        game.play().unwrap();
+
+        assert_eq!(game.guesses(), 1);
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains("Please guess a number in [0, 100]")));
+    }
+
+    #[test]
+    fn play_game_warns_about_guesses_already_ruled_out() {
+        let guesses = ["50", "60", "40"];
+        let (mut writer, reader) = setup_io_with_many_inputs(&guesses);
+        let mut game = Game::new(40, &mut writer, reader);
+        game.play().unwrap();
+
+        assert_eq!(game.guesses(), 3);
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains("60 was already ruled out")));
+    }
+
+    #[test]
+    fn game_state_check_rejects_guesses_out_of_range_or_repeated() {
+        let mut state = GameState::new(40, Settings::default());
+        state.guess(50);
+
+        assert_eq!(state.check(101), Err(Rejection::OutOfRange));
+        assert_eq!(state.check(50), Err(Rejection::Repeated));
+        // ruled out, but still counts
+        assert_eq!(state.check(60), Ok(()));
+        assert_eq!(state.check(40), Ok(()));
+    }
+
+    #[test]
+    fn game_state_records_history_and_narrows_interval() {
+        let mut state = GameState::new(40, Settings::default());
+
+        assert_eq!(state.guess(50).direction, Direction::TooHigh);
+        assert_eq!(state.guess(20).direction, Direction::TooLow);
+        assert_eq!(state.interval(), (21, 49));
+        assert_eq!(state.guess(40).direction, Direction::Correct);
+        assert_eq!(state.interval(), (40, 40));
+        let guesses: Vec<usize> = state.history().iter().map(|f| f.guess).collect();
+        assert_eq!(guesses, vec![50, 20, 40]);
+        assert!(state.is_won());
+    }
+
+    #[test]
+    fn game_state_interval_ignores_guesses_already_ruled_out() {
+        let mut state = GameState::new(40, Settings::default());
+        state.guess(30);
+        state.guess(10);
+
+        assert_eq!(state.interval(), (31, MAX_SECRET));
+    }
+
+    #[test]
+    fn game_state_is_over_when_guesses_run_out() {
+        let settings = Settings {
+            max_guesses: Some(2),
+            ..Settings::default()
+        };
+        let mut state = GameState::new(40, settings);
+        state.guess(1);
+
+        assert_eq!(state.guesses_left(), Some(1));
+        assert!(!state.is_over());
+        state.guess(2);
+        assert!(state.is_over());
+        assert!(!state.is_won());
+    }
+
+    #[test]
+    fn game_state_is_over_when_won() {
+        let mut state = GameState::new(40, Settings::default());
+        state.guess(40);
+
+        assert!(state.is_over());
+        assert_eq!(state.guesses_left(), None);
+    }
+
+    fn evaluate(guess: usize, secret: usize) -> Feedback {
+        GameState::new(secret, Settings::default()).evaluate(guess)
+    }
+
+    #[test]
+    fn evaluate_returns_correct_if_guess_is_correct() {
+        assert!(evaluate(1, 1).is_correct())
+    }
+
+    #[test]
+    fn evaluate_returns_incorrect_if_guess_is_incorrect() {
+        assert!(!evaluate(1, 2).is_correct())
+    }
+
+    #[test]
+    fn evaluate_specifies_if_guess_is_too_high() {
+        assert_eq!(evaluate(11, 10).direction, Direction::TooHigh)
+    }
+
+    #[test]
+    fn evaluate_specifies_if_guess_is_too_low() {
+        assert_eq!(evaluate(9, 10).direction, Direction::TooLow)
+    }
+
+    #[test]
+    fn evaluate_gives_distance_only_for_bounded_misses() {
+        assert_eq!(evaluate(9, 10).distance, Some(Distance::Hot));
+        assert_eq!(evaluate(10, 10).distance, None);
+        assert_eq!(GameState::unbounded(10, None).guess(9).distance, None);
     }
 }
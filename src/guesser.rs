@@ -0,0 +1,198 @@
+use std::{
+    io::{BufRead, Write},
+    num::ParseIntError,
+};
+
+use crate::{
+    constants::{MAX_SECRET, MIN_SECRET},
+    feedback::{render, Direction, Feedback},
+    game::GameError,
+    io::{prompt, write, WriteArgs},
+};
+
+/// Anything that can play a game by proposing guesses & learning from the
+/// feedback each guess receives, be it a human at a terminal or an AI player.
+pub trait Guesser {
+    /// Propose the next guess. Returns Err if the Guesser wants to stop
+    /// playing (e.g. a human entering "quit").
+    fn guess(&mut self) -> Result<usize, GameError>;
+
+    /// Receive the Feedback for the most recent guess. Returns Err if the
+    /// Guesser can't take it in (e.g. a human's terminal has closed).
+    fn feedback(&mut self, feedback: Feedback) -> Result<(), GameError>;
+}
+
+impl<G: Guesser + ?Sized> Guesser for Box<G> {
+    fn guess(&mut self) -> Result<usize, GameError> {
+        (**self).guess()
+    }
+
+    fn feedback(&mut self, feedback: Feedback) -> Result<(), GameError> {
+        (**self).feedback(feedback)
+    }
+}
+
+/// A Guesser driven by a human, reading guesses from the given read stream &
+/// writing prompts & feedback to the given write stream.
+pub struct HumanGuesser<W: Write, R: BufRead> {
+    guesses: usize,
+    max: usize,
+    min: usize,
+    reader: R,
+    writer: W,
+}
+
+impl<W: Write, R: BufRead> HumanGuesser<W, R> {
+    /// Create a new HumanGuesser with the given io streams, guessing within
+    /// MIN & MAX values from crate::constants.
+    pub fn new(writer: W, reader: R) -> Self {
+        Self::with_range(MIN_SECRET, MAX_SECRET, writer, reader)
+    }
+
+    /// Create a new HumanGuesser with the given io streams, guessing within
+    /// the inclusive range [min, max].
+    pub fn with_range(min: usize, max: usize, writer: W, reader: R) -> Self {
+        HumanGuesser {
+            guesses: 0,
+            max,
+            min,
+            writer,
+            reader,
+        }
+    }
+
+    /// Number of guesses the human has made so far.
+    pub fn guesses(&self) -> usize {
+        self.guesses
+    }
+}
+
+impl<W: Write, R: BufRead> Guesser for HumanGuesser<W, R> {
+    /// Prompt the human for a guess in a loop until they enter an integer or
+    /// "quit".
+    fn guess(&mut self) -> Result<usize, GameError> {
+        loop {
+            // prompt for guess
+            write(&mut self.writer, WriteArgs::Str("Guess a number...\n"))?;
+            let guess_value = prompt(&mut self.writer, &mut self.reader)?;
+            let guess_parsed: Result<usize, ParseIntError> = guess_value.parse();
+
+            match guess_parsed {
+                Ok(guess) => {
+                    self.guesses += 1;
+                    return Ok(guess);
+                }
+                // return error if guess is "quit"
+                Err(_) => {
+                    if let "quit" = guess_value.as_str() {
+                        write(&mut self.writer, WriteArgs::Str("Quitting...\n"))?;
+                        return Err(GameError::Quit);
+                    } else {
+                        write(
+                            &mut self.writer,
+                            WriteArgs::Fmt(format_args!(
+                                "Invalid input, please guess an integer belonging to [{},{}] or enter 'quit' to quit playing.\n",
+                                self.min, self.max
+                            )),
+                        )?;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Tell the human how their guess compared to the secret.
+    fn feedback(&mut self, feedback: Feedback) -> Result<(), GameError> {
+        let end = match feedback.direction {
+            Direction::Correct => " ",
+            _ => "\n\n",
+        };
+        write(
+            &mut self.writer,
+            WriteArgs::Fmt(format_args!("{}{}", render(&feedback), end)),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::test_utils::{setup_io_with_input, setup_io_with_many_inputs};
+
+    use super::*;
+
+    #[test]
+    fn human_guess_returns_parsed_input() {
+        let (writer, reader) = setup_io_with_input("42");
+        let mut guesser = HumanGuesser::new(writer, reader);
+
+        assert_eq!(guesser.guess().unwrap(), 42)
+    }
+
+    #[test]
+    fn human_guess_reprompts_after_invalid_input() {
+        let inputs = ["not a number", "7"];
+        let (writer, reader) = setup_io_with_many_inputs(&inputs);
+        let mut guesser = HumanGuesser::new(writer, reader);
+
+        assert_eq!(guesser.guess().unwrap(), 7)
+    }
+
+    #[test]
+    fn human_guess_counts_only_valid_guesses() {
+        let inputs = ["not a number", "7", "8"];
+        let (writer, reader) = setup_io_with_many_inputs(&inputs);
+        let mut guesser = HumanGuesser::new(writer, reader);
+        guesser.guess().unwrap();
+        guesser.guess().unwrap();
+
+        assert_eq!(guesser.guesses(), 2)
+    }
+
+    #[test]
+    fn human_guess_returns_quit_if_user_enters_quit() {
+        let (writer, reader) = setup_io_with_input("quit");
+        let mut guesser = HumanGuesser::new(writer, reader);
+
+        assert!(matches!(guesser.guess(), Err(GameError::Quit)))
+    }
+
+    #[test]
+    fn human_guess_returns_io_error_if_input_runs_out() {
+        let (writer, reader) = setup_io_with_many_inputs(&["not a number"]);
+        let mut guesser = HumanGuesser::new(writer, reader);
+
+        assert!(matches!(guesser.guess(), Err(GameError::Io(_))))
+    }
+
+    #[test]
+    fn human_feedback_tells_user_if_guess_is_too_high() {
+        let (mut writer, reader) = setup_io_with_input("1");
+        let mut guesser = HumanGuesser::new(&mut writer, reader);
+        guesser
+            .feedback(Feedback::new(11, Direction::TooHigh))
+            .unwrap();
+
+        assert!(writer
+            .written_lines
+            .last()
+            .unwrap()
+            .contains("11 is too high"));
+    }
+
+    #[test]
+    fn human_feedback_tells_user_if_guess_is_too_low() {
+        let (mut writer, reader) = setup_io_with_input("1");
+        let mut guesser = HumanGuesser::new(&mut writer, reader);
+        guesser
+            .feedback(Feedback::new(9, Direction::TooLow))
+            .unwrap();
+
+        assert!(writer
+            .written_lines
+            .last()
+            .unwrap()
+            .contains("9 is too low"));
+    }
+}
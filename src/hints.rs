@@ -0,0 +1,211 @@
+/// The kinds of hint a player can ask for during a round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintKind {
+    Parity,
+    Divisor,
+    Near,
+    DigitSum,
+    NextGuess,
+}
+
+impl HintKind {
+    /// Every kind of hint, in the order they're offered.
+    pub const ALL: [HintKind; 5] = [
+        HintKind::Parity,
+        HintKind::Divisor,
+        HintKind::Near,
+        HintKind::DigitSum,
+        HintKind::NextGuess,
+    ];
+
+    /// Name the player enters to ask for this kind of hint, e.g.
+    /// `/hint parity`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            HintKind::Parity => "parity",
+            HintKind::Divisor => "divisor",
+            HintKind::Near => "near",
+            HintKind::DigitSum => "digits",
+            HintKind::NextGuess => "next",
+        }
+    }
+
+    /// Find the kind of hint with the given name.
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// Points taken off the round's score for taking this kind of hint.
+    pub fn cost(&self) -> u32 {
+        match self {
+            HintKind::Parity => 50,
+            HintKind::Divisor => 50,
+            HintKind::DigitSum => 75,
+            HintKind::Near => 100,
+            HintKind::NextGuess => 150,
+        }
+    }
+
+    /// What this kind of hint tells the player.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            HintKind::Parity => "whether the secret is even or odd",
+            HintKind::Divisor => "whether the secret is divisible by a small number",
+            HintKind::Near => "a number the secret is near",
+            HintKind::DigitSum => "what the secret's digits add up to",
+            HintKind::NextGuess => "the best next guess",
+        }
+    }
+}
+
+/// A hint taken during a round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TakenHint {
+    pub kind: HintKind,
+    pub hint: Hint,
+    /// Number of guesses made before it was taken
+    pub after: usize,
+}
+
+/// Divisors asked about by divisor hints, in turn.
+const DIVISORS: [usize; 5] = [3, 4, 5, 7, 11];
+
+/// A hint about the secret, worked out from the secret & what the player
+/// knows so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    Parity { even: bool },
+    Divisor { divisor: usize, divisible: bool },
+    Near { center: usize, distance: usize },
+    DigitSum(usize),
+    NextGuess(usize),
+}
+
+impl Hint {
+    /// Work out a hint of the given kind about the secret, which the player
+    /// knows is in the inclusive interval [low, high]. `taken` is the number
+    /// of hints of this kind already taken, so repeated hints tell the player
+    /// something new where they can.
+    pub fn new(kind: HintKind, secret: usize, (low, high): (usize, usize), taken: usize) -> Self {
+        match kind {
+            HintKind::Parity => Hint::Parity {
+                even: secret.is_multiple_of(2),
+            },
+            HintKind::Divisor => {
+                let divisor = DIVISORS[taken % DIVISORS.len()];
+                Hint::Divisor {
+                    divisor,
+                    divisible: secret.is_multiple_of(divisor),
+                }
+            }
+            HintKind::Near => {
+                // split the interval into windows about an eighth as wide,
+                // halving each time it's asked for, & give the centre of the
+                // one the secret is in, kept inside the interval when the
+                // last window runs past its end
+                let distance = ((high - low) / (8 << taken.min(8))).max(1);
+                let width = distance.saturating_mul(2).saturating_add(1);
+                let start = low + (secret - low) / width * width;
+                let center = start.saturating_add(distance).min(high);
+                Hint::Near { center, distance }
+            }
+            HintKind::DigitSum => Hint::DigitSum(digit_sum(secret)),
+            HintKind::NextGuess => Hint::NextGuess(low + (high - low) / 2),
+        }
+    }
+}
+
+fn digit_sum(mut n: usize) -> usize {
+    let mut sum = 0;
+    while n > 0 {
+        sum += n % 10;
+        n /= 10;
+    }
+    sum
+}
+
+/// Render a Hint as the text shown to a human player.
+pub fn render(hint: &Hint) -> String {
+    match hint {
+        Hint::Parity { even: true } => String::from("The secret is even."),
+        Hint::Parity { even: false } => String::from("The secret is odd."),
+        Hint::Divisor {
+            divisor,
+            divisible: true,
+        } => format!("The secret is divisible by {}.", divisor),
+        Hint::Divisor {
+            divisor,
+            divisible: false,
+        } => format!("The secret isn't divisible by {}.", divisor),
+        Hint::Near { center, distance } => {
+            format!("The secret is within {} of {}.", distance, center)
+        }
+        Hint::DigitSum(sum) => format!("The secret's digits add up to {}.", sum),
+        Hint::NextGuess(guess) => format!("The best next guess is {}.", guess),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_round_trip_through_names() {
+        for kind in HintKind::ALL {
+            assert_eq!(HintKind::parse(kind.name()), Some(kind));
+        }
+        assert_eq!(HintKind::parse("everything"), None);
+    }
+
+    #[test]
+    fn near_hint_contains_secret() {
+        for secret in 0..=100 {
+            for taken in 0..3 {
+                match Hint::new(HintKind::Near, secret, (0, 100), taken) {
+                    Hint::Near { center, distance } => {
+                        assert!(secret.abs_diff(center) <= distance)
+                    }
+                    other => panic!("expected a Near hint, got {:?}", other),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn near_hint_stays_in_interval_at_top_of_range() {
+        let top = usize::MAX - 1;
+        for interval in [(0, top), (top - 100, top), (usize::MAX - 3, usize::MAX)] {
+            for secret in (interval.1 - 20).max(interval.0)..=interval.1 {
+                for taken in 0..3 {
+                    match Hint::new(HintKind::Near, secret, interval, taken) {
+                        Hint::Near { center, distance } => {
+                            assert!((interval.0..=interval.1).contains(&center));
+                            assert!(secret.abs_diff(center) <= distance);
+                        }
+                        other => panic!("expected a Near hint, got {:?}", other),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn repeated_divisor_hints_ask_about_new_divisors() {
+        let first = Hint::new(HintKind::Divisor, 12, (0, 100), 0);
+        let second = Hint::new(HintKind::Divisor, 12, (0, 100), 1);
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn render_describes_hints() {
+        assert_eq!(
+            render(&Hint::new(HintKind::DigitSum, 47, (0, 100), 0)),
+            "The secret's digits add up to 11."
+        );
+        assert_eq!(
+            render(&Hint::new(HintKind::NextGuess, 47, (20, 60), 0)),
+            "The best next guess is 40."
+        );
+    }
+}
@@ -1,11 +1,14 @@
 use std::{
-    fmt::Arguments,
-    io::{BufRead, Write},
+    error,
+    fmt::{self, Arguments},
+    io::{BufRead, Error, ErrorKind, Result, Write},
 };
 
 /// Get user input from write stream (e.g. stdin) & return it as a String.
 ///
 /// Takes a write stream & a read stream, implementing Write & BufRead.
+/// Returns Err if either stream fails, or with an UnexpectedEof error if the
+/// read stream has no more input (e.g. stdin was closed).
 ///
 /// # Example
 ///
@@ -13,21 +16,23 @@ use std::{
 /// let mut output = stdout();
 /// let stdin = stdin();
 /// let mut input = stdin.lock();
-/// let user_input = prompt(&mut output, &mut input)
+/// let user_input = prompt(&mut output, &mut input)?;
 /// // do something with input...
-pub fn prompt(mut writer: impl Write, mut reader: impl BufRead) -> String {
+pub fn prompt(mut writer: impl Write, mut reader: impl BufRead) -> Result<String> {
     let mut answer = String::new();
 
     // print the prompt char
-    write(&mut writer, WriteArgs::Str("> "));
+    write(&mut writer, WriteArgs::Str("> "))?;
 
     // get the user's response
-    reader.read_line(&mut answer).unwrap();
+    if reader.read_line(&mut answer)? == 0 {
+        return Err(Error::new(ErrorKind::UnexpectedEof, "no more input"));
+    }
 
     // pad w/ empty line
-    write(&mut writer, WriteArgs::Str("\n"));
+    write(&mut writer, WriteArgs::Str("\n"))?;
 
-    answer.trim().to_string()
+    Ok(answer.trim().to_string())
 }
 
 /// The types of data allowable as output to give to `write()`
@@ -51,14 +56,44 @@ pub enum WriteArgs<'a> {
 /// # Example
 /// // get stdin & stdout reader & writer
 /// let mut output = stdout();
-/// write(&mut output, WriteArgs::Str("Hello World!"))
+/// write(&mut output, WriteArgs::Str("Hello World!"))?;
 /// // prints "Hello World!" to stdout
-pub fn write(mut writer: impl Write, args: WriteArgs) {
+pub fn write(mut writer: impl Write, args: WriteArgs) -> Result<()> {
     match args {
-        WriteArgs::Fmt(x) => writer.write_fmt(x).unwrap(),
-        WriteArgs::Str(x) => writer.write_fmt(format_args!("{}", x)).unwrap(),
+        WriteArgs::Fmt(x) => writer.write_fmt(x)?,
+        WriteArgs::Str(x) => writer.write_fmt(format_args!("{}", x))?,
     }
-    writer.flush().unwrap();
+    writer.flush()
+}
+
+/// Whether the given error just means the read stream ran out of input,
+/// rather than something genuinely going wrong.
+pub fn is_eof(err: &Error) -> bool {
+    err.kind() == ErrorKind::UnexpectedEof
+}
+
+/// Error payload marking a read cut short by the player pressing Ctrl-C.
+#[derive(Debug)]
+struct Interrupt;
+
+impl fmt::Display for Interrupt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "interrupted")
+    }
+}
+
+impl error::Error for Interrupt {}
+
+/// The error a read stream returns when the player presses Ctrl-C, see
+/// crate::signal. It isn't ErrorKind::Interrupted, which `read_line` would
+/// quietly retry.
+pub fn interrupted() -> Error {
+    Error::other(Interrupt)
+}
+
+/// Whether the given error means the player pressed Ctrl-C.
+pub fn is_interrupt(err: &Error) -> bool {
+    err.get_ref().is_some_and(|inner| inner.is::<Interrupt>())
 }
 
 #[cfg(test)]
@@ -68,18 +103,61 @@ mod tests {
     #[test]
     fn prompt_sends_prompt_char_to_given_print_fn() {
         let (mut writer, reader) = test_utils::setup_io();
-        prompt(&mut writer, reader);
+        prompt(&mut writer, reader).unwrap();
 
-        assert_eq!(writer.written_lines.get(0), Some(&("> ").to_string()));
+        assert_eq!(writer.written_lines.first(), Some(&("> ").to_string()));
     }
 
     #[test]
     fn prompt_returns_user_input() {
         let (writer, reader) = test_utils::setup_io_with_input("given input");
-        let actual = prompt(writer, reader);
+        let actual = prompt(writer, reader).unwrap();
 
         assert_eq!(actual, String::from("given input"))
     }
+
+    #[test]
+    fn prompt_returns_eof_error_when_input_runs_out() {
+        let (writer, reader) = test_utils::setup_io_with_many_inputs(&[]);
+        let err = prompt(writer, reader).unwrap_err();
+
+        assert!(is_eof(&err))
+    }
+
+    #[test]
+    fn prompt_returns_interrupt_error_on_ctrl_c() {
+        let inputs = [test_utils::INTERRUPT];
+        let (writer, reader) = test_utils::setup_io_with_many_inputs(&inputs);
+        let err = prompt(writer, reader).unwrap_err();
+
+        assert!(is_interrupt(&err));
+        assert!(!is_eof(&err));
+    }
+
+    #[test]
+    fn prompt_allows_empty_lines() {
+        let (writer, reader) = test_utils::setup_io_with_input("");
+
+        assert_eq!(prompt(writer, reader).unwrap(), String::new())
+    }
+
+    #[test]
+    fn write_returns_err_if_stream_fails() {
+        struct Broken;
+
+        impl Write for Broken {
+            fn write(&mut self, _buf: &[u8]) -> Result<usize> {
+                Err(Error::from(ErrorKind::BrokenPipe))
+            }
+
+            fn flush(&mut self) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let err = write(Broken, WriteArgs::Str("Hello")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe)
+    }
 }
 
 /// Testing utilities for working with Write & Read streams
@@ -87,9 +165,13 @@ mod tests {
 pub mod test_utils {
     use std::{
         fmt::{write, Arguments, Result as FmtResult, Write as FmtWrite},
-        io::{BufRead, Error, ErrorKind, Read, Result as IoResult, Write},
+        io::{BufRead, Error, Read, Result as IoResult, Write},
     };
 
+    /// Input that makes a TestReader fail as if the player pressed Ctrl-C
+    /// instead of entering a line.
+    pub const INTERRUPT: &str = "^C";
+
     /// Setup a Write stream & a Read stream for testing with user input of "1"
     ///
     /// # Example
@@ -98,7 +180,7 @@ pub mod test_utils {
     ///
     /// write(&mut writer, WriteArgs::Str("Hello World!"));
     /// assert_eq!(
-    ///     writer.written_lines.get(0).unwrap(),
+    ///     writer.written_lines.first().unwrap(),
     ///     String::from("Hello World!")
     /// );
     ///
@@ -211,7 +293,7 @@ pub mod test_utils {
                     if output.error.is_err() {
                         output.error
                     } else {
-                        Err(Error::new(ErrorKind::Other, "formatter error"))
+                        Err(Error::other("formatter error"))
                     }
                 }
             }
@@ -223,7 +305,7 @@ pub mod test_utils {
                 self.line_to_write = None;
                 Ok(())
             } else {
-                Err(Error::new(ErrorKind::Other, "Nothing to write!"))
+                Err(Error::other("Nothing to write!"))
             }
         }
     }
@@ -261,15 +343,21 @@ pub mod test_utils {
             match &self.values {
                 ReaderValues::One(value) => {
                     buf.push_str(value.as_str());
+                    buf.push('\n');
                     Ok(buf.len())
                 }
                 ReaderValues::Many(values) => {
                     if let Some(value) = values.get(self.next_call) {
                         self.next_call += 1;
+                        if value == INTERRUPT {
+                            return Err(super::interrupted());
+                        }
                         buf.push_str(value.as_str());
+                        buf.push('\n');
                         Ok(buf.len())
                     } else {
-                        Err(Error::new(ErrorKind::Other, "No more values to read."))
+                        // no more values to read, like a closed stdin
+                        Ok(0)
                     }
                 }
             }
@@ -293,7 +381,7 @@ pub mod test_utils {
                         buf.push_str(value.as_str());
                         Ok(buf.len())
                     } else {
-                        Err(Error::new(ErrorKind::Other, "No more values to read."))
+                        Ok(0)
                     }
                 }
             }
@@ -0,0 +1,415 @@
+use std::{fmt, iter::Peekable, str::Chars};
+
+/// A JSON value, just enough for the line based protocols to read commands
+/// & write events without scraping text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    /// A number as written, so integers of any size survive a round trip
+    Number(String),
+    String(String),
+    Array(Vec<Value>),
+    /// Fields in the order they were written
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// The value of the given field, if this is an object that has it.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The value as a non-negative integer, if it is one.
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Value::Number(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Build an object from its fields, e.g.
+/// `object(vec![("event", "won".into()), ("guesses", 5.into())])`.
+pub fn object(fields: Vec<(&str, Value)>) -> Value {
+    Value::Object(
+        fields
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    )
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+macro_rules! from_integer {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Value {
+            fn from(value: $t) -> Self {
+                Value::Number(value.to_string())
+            }
+        })*
+    };
+}
+
+from_integer!(u32, u64, u128, usize);
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(values: Vec<T>) -> Self {
+        Value::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl fmt::Display for Value {
+    /// Write the value as compact JSON, on one line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write_string(s, f),
+            Value::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(key, f)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(s: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Deepest nesting of arrays & objects `parse` accepts, so a hostile line
+/// can't overflow the stack.
+pub const MAX_DEPTH: usize = 64;
+
+/// Parse a JSON document, e.g. one line of a protocol.
+pub fn parse(text: &str) -> Result<Value, String> {
+    let mut parser = Parser {
+        chars: text.chars().peekable(),
+        depth: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    match parser.chars.next() {
+        None => Ok(value),
+        Some(c) => Err(format!("Unexpected '{}' after the value", c)),
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    /// Arrays & objects the parser is inside of
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_ascii_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("Expected '{}', found '{}'", expected, c)),
+            None => Err(format!("Expected '{}', found the end", expected)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('{') => self.nested(Self::object),
+            Some('[') => self.nested(Self::array),
+            Some('"') => self.string().map(Value::String),
+            Some('-' | '0'..='9') => self.number(),
+            Some('t') => self.literal("true", Value::Bool(true)),
+            Some('f') => self.literal("false", Value::Bool(false)),
+            Some('n') => self.literal("null", Value::Null),
+            Some(c) => Err(format!("Unexpected '{}'", c)),
+            None => Err(String::from("Expected a value, found the end")),
+        }
+    }
+
+    /// Parse an array or object with the given function, unless that would
+    /// nest deeper than MAX_DEPTH.
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Value, String>) -> Result<Value, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!(
+                "Values can't be nested more than {} deep",
+                MAX_DEPTH
+            ));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, String> {
+        for expected in word.chars() {
+            self.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let mut number = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            number.push(c);
+        }
+
+        match is_number(&number) {
+            true => Ok(Value::Number(number)),
+            false => Err(format!("'{}' isn't a number", number)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => string.push(self.escape()?),
+                Some(c) if c.is_control() => {
+                    return Err(String::from("Strings can't contain control characters"))
+                }
+                Some(c) => string.push(c),
+                None => return Err(String::from("Unterminated string")),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, String> {
+        match self.chars.next() {
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('/') => Ok('/'),
+            Some('b') => Ok('\u{8}'),
+            Some('f') => Ok('\u{c}'),
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('t') => Ok('\t'),
+            Some('u') => {
+                let high = self.hex()?;
+                let code = match high {
+                    0xd800..=0xdbff => {
+                        self.expect('\\')?;
+                        self.expect('u')?;
+                        match self.hex()? {
+                            low @ 0xdc00..=0xdfff => {
+                                0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                            }
+                            _ => return Err(String::from("Invalid unicode surrogate pair")),
+                        }
+                    }
+                    _ => high,
+                };
+                char::from_u32(code).ok_or_else(|| String::from("Invalid unicode escape"))
+            }
+            _ => Err(String::from("Invalid escape")),
+        }
+    }
+
+    fn hex(&mut self) -> Result<u32, String> {
+        let digits: String = (0..4).filter_map(|_| self.chars.next()).collect();
+        u32::from_str_radix(&digits, 16).map_err(|_| format!("'{}' isn't a unicode escape", digits))
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&']').is_some() {
+            return Ok(Value::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Value::Array(values)),
+                _ => return Err(String::from("Expected ',' or ']' in an array")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'}').is_some() {
+            return Ok(Value::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Value::Object(fields)),
+                _ => return Err(String::from("Expected ',' or '}' in an object")),
+            }
+        }
+    }
+}
+
+/// Whether the text follows JSON's number syntax: an optional minus, an
+/// integer part without leading zeros, then optional fraction & exponent.
+fn is_number(text: &str) -> bool {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let text = text.strip_prefix('-').unwrap_or(text);
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (text, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+
+    digits(integer)
+        && (integer == "0" || !integer.starts_with('0'))
+        && fraction.is_none_or(digits)
+        && exponent.is_none_or(|e| digits(e.strip_prefix(['+', '-']).unwrap_or(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_nested_values() {
+        let value =
+            parse(r#" {"command": "guess", "value": 42, "tags": [true, null, -1.5e3]} "#).unwrap();
+
+        assert_eq!(value.get("command").and_then(Value::as_str), Some("guess"));
+        assert_eq!(value.get("value").and_then(Value::as_usize), Some(42));
+        assert_eq!(
+            value.get("tags"),
+            Some(&Value::Array(vec![
+                Value::Bool(true),
+                Value::Null,
+                Value::Number(String::from("-1.5e3"))
+            ]))
+        );
+    }
+
+    #[test]
+    fn parse_rejects_malformed_json() {
+        for text in ["", "{", "{\"a\":}", "[1,]", "\"open", "01x", "{} {}", "+1"] {
+            assert!(parse(text).is_err(), "{:?} should be rejected", text);
+        }
+    }
+
+    #[test]
+    fn parse_follows_number_syntax() {
+        for text in ["0", "-0", "10", "0.5", "-1.5e3", "2E+8", "7e-2"] {
+            assert_eq!(parse(text), Ok(Value::Number(text.to_string())));
+        }
+        for text in ["01", "-01", "00", "1.", ".5", "1.e3", "1e", "--1", "1-"] {
+            assert!(parse(text).is_err(), "{:?} should be rejected", text);
+        }
+    }
+
+    #[test]
+    fn parse_limits_nesting_depth() {
+        let nested = |depth| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        assert!(parse(&nested(MAX_DEPTH + 1)).is_err());
+        assert!(parse(&"[{\"a\":".repeat(100_000)).is_err());
+    }
+
+    #[test]
+    fn parse_rejects_invalid_surrogates() {
+        assert_eq!(parse(r#""\ud83d\ude00""#), Ok(Value::from("😀")));
+        for text in [r#""\ud83d\u0041""#, r#""\ud83d\ud83d""#, r#""\ude00""#] {
+            assert!(parse(text).is_err(), "{:?} should be rejected", text);
+        }
+    }
+
+    #[test]
+    fn values_round_trip_through_text() {
+        let value = object(vec![
+            ("event", "hint".into()),
+            ("text", "say \"hi\"\n\u{1}é😀".into()),
+            ("secret", u64::MAX.into()),
+            ("limit", None::<usize>.into()),
+        ]);
+        let text = value.to_string();
+
+        assert!(!text.contains('\n'));
+        assert_eq!(parse(&text), Ok(value));
+        assert_eq!(parse(r#""😀""#), Ok(Value::from("😀")));
+    }
+}
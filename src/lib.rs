@@ -0,0 +1,46 @@
+//! A number guessing game, along with AI players that guess with different
+//! strategies & tools for comparing them.
+//!
+//! The rules of a round live in [`GameState`], free of any I/O. [`Game`]
+//! plays a round on top of it against any [`Guesser`], whether a human at the
+//! console ([`HumanGuesser`]) or one of the AI [`players`]. Secrets come from
+//! any [`SecretSource`], & every prompt & message goes through the helpers in
+//! [`io`], so a game can be embedded with whatever streams a tool has to hand.
+
+pub mod analysis;
+pub mod bench;
+pub mod cli;
+pub mod commands;
+pub mod constants;
+pub mod difficulty;
+pub mod engine;
+pub mod feedback;
+pub mod game;
+pub mod guesser;
+pub mod hints;
+pub mod io;
+pub mod json;
+pub mod menu;
+pub mod players;
+pub mod protocol;
+pub mod random;
+pub mod recording;
+pub mod replay;
+pub mod reverse;
+pub mod save;
+pub mod scores;
+pub mod scoring;
+pub mod server;
+pub mod session;
+pub mod signal;
+pub mod summary;
+
+pub use crate::{
+    difficulty::{Difficulty, Settings},
+    feedback::{Direction, Distance, Feedback},
+    game::{play_limited, play_round, play_with, Game, GameError, GameState},
+    guesser::{Guesser, HumanGuesser},
+    io::{prompt, write, WriteArgs},
+    menu::{Menu, MenuAction, MenuError},
+    random::{NumberGenerator, ScriptedSecrets, SecretSource},
+};
@@ -1,80 +1,210 @@
-use std::io::{stdin, stdout};
-
-use crate::{
-    constants::{INVALID_CHOICE, MAX_SECRET, MIN_SECRET},
-    game::{Game, GameError},
-    io::{write, WriteArgs},
-    menu::menu,
-    random::NumberGenerator,
+use std::{
+    env,
+    io::{stdin, stdout, ErrorKind, Result},
+    path::PathBuf,
+    process,
 };
 
-pub mod constants;
-pub mod game;
-pub mod io;
-pub mod menu;
-pub mod random;
+use guessing_game::{
+    analysis::{self, AnalysisConfig},
+    bench::{self, BenchConfig},
+    cli::{self, Command, PlayConfig, ReplayConfig, ScoresConfig, ServeConfig},
+    constants::{MAX_SECRET, MIN_SECRET},
+    difficulty::{Difficulty, Settings},
+    io::{is_eof, is_interrupt},
+    protocol::{self, Interface},
+    random::{self, NumberGenerator},
+    recording::Recording,
+    replay,
+    scores::{self, Scoreboard},
+    server::Server,
+    session,
+    signal::{self, Interruptible},
+};
 
 /// Main
 ///
-/// Get I/O streams & set up loop for running game repeatedly
+/// Run the command given on the command line, playing interactively if none
+/// is given. See `guessing_game --help` or guessing_game::cli for the options.
 fn main() {
-    // get stdin & stdout reader & writer
-    let mut output = stdout();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = match cli::parse(&args) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(2);
+        }
+    };
+
+    match command {
+        Command::Play(config) => play_main(config),
+        Command::Simulate(config) => simulate_main(config),
+        Command::Analyze(config) => analyze_main(config),
+        Command::Scores(config) => scores_main(config),
+        Command::Replay(config) => replay_main(config),
+        Command::Serve(config) => serve_main(config),
+        Command::Help(help) => print!("{}", help),
+    }
+}
+
+/// The seed given on the command line, else the one set in the environment,
+/// else a random one.
+fn resolve_seed(seed: Option<u64>) -> u64 {
+    let from_env = random::seed_from_env().unwrap_or_else(|reason| {
+        eprintln!("{}: {}", random::SEED_ENV, reason);
+        process::exit(2);
+    });
+
+    seed.or(from_env).unwrap_or_else(rand::random)
+}
+
+/// Load the scoreboard kept at the given path, or the default one, refusing
+/// to go on rather than risk overwriting a file that couldn't be read.
+fn load_scoreboard(path: Option<PathBuf>) -> Scoreboard {
+    let path = path.unwrap_or_else(scores::default_path);
+    match Scoreboard::load(&path) {
+        Ok(scoreboard) => scoreboard,
+        Err(err) => {
+            eprintln!("Couldn't read scores from {}: {}", path.display(), err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Interactive play
+///
+/// Running out of input or choosing to exit after Ctrl-C ends the session
+/// cleanly, any other I/O failure exits with a non-zero code. Either way the
+/// session's summary has been printed & every round saved to the scoreboard.
+fn play_main(config: PlayConfig) {
+    let seed = resolve_seed(config.seed);
+    if config.protocol == Interface::Jsonl {
+        return protocol_main(config.settings, seed);
+    }
+    let scoreboard = load_scoreboard(config.scores);
+    if let Err(err) = signal::install() {
+        eprintln!("Couldn't catch Ctrl-C: {}", err);
+    }
+
+    let rnd = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, seed);
     let stdin = stdin();
-    let mut input = stdin.lock();
-    // get secret number generator
-    let mut rnd = NumberGenerator::new(MIN_SECRET, MAX_SECRET);
-
-    // greet the user
-    write(
-        &mut output,
-        WriteArgs::Str("Welcome to the guessing game!\n\n"),
+    let result = session::run(
+        config.settings,
+        config.player,
+        rnd,
+        scoreboard,
+        config.record,
+        stdout(),
+        Interruptible::new(stdin.lock()),
     );
 
-    // set up the loop
-    let mut playing: bool = true;
-    // enter loop
-    while playing {
-        // render menu
-        let choices = ["play game", "exit"];
-        let res = menu(&choices, &mut output, &mut input);
-
-        // init new game
-        let secret = rnd.gen_secret();
-        let mut game = Game::new(secret, &mut output, &mut input);
-
-        // handle user choice
-        match res {
-            Ok(choice) => {
-                match choice {
-                    // play game -> enter game
-                    1 => {
-                        let game_result = game.play();
-                        if let Err(value) = game_result {
-                            match value {
-                                GameError::Quit => {
-                                    write(&mut output, WriteArgs::Str("You quit. "));
-                                }
-                                GameError::Unknown => {
-                                    write(
-                                        &mut output,
-                                        WriteArgs::Str("An unknown Error occurred."),
-                                    );
-                                }
-                            }
-                        } else {
-                            write(&mut output, WriteArgs::Str("You won!\n"));
-                        }
-
-                        write(&mut output, WriteArgs::Str("Play again?\n"));
-                    }
-                    // exit -> exit loop
-                    2 => playing = false,
-                    // not an allowable input
-                    _ => println!("{}", INVALID_CHOICE),
-                }
-            }
-            Err(reason) => println!("{}", reason),
+    match result {
+        Ok(()) => (),
+        Err(err) if is_eof(&err) || is_interrupt(&err) => println!("\nGoodbye!"),
+        Err(err) => {
+            eprintln!("\n{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Machine interface
+///
+/// Let another program play over stdin & stdout, one JSON object per line,
+/// see guessing_game::protocol::run for the commands & events.
+fn protocol_main(settings: Option<Settings>, seed: u64) {
+    let rnd = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, seed);
+    let stdin = stdin();
+    exit_on_error(protocol::run(settings, rnd, stdout(), stdin.lock()));
+}
+
+/// Headless benchmark
+///
+/// Play every AI strategy, & any external engines, against generated
+/// secrets & print a report, e.g.
+/// `guessing_game simulate --games 1000 --cap 7 --format csv`
+fn simulate_main(config: BenchConfig) {
+    let seed = resolve_seed(config.seed);
+    eprintln!("Seed: {}", seed);
+    let mut rnd = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, seed);
+    let secrets = bench::draw_secrets(&config, &mut rnd);
+    let mut reports = bench::run(&config, &secrets, &mut rnd);
+    match bench::run_engines(&config, &secrets) {
+        Ok(engine_reports) => reports.extend(engine_reports),
+        Err(reason) => {
+            eprintln!("{}", reason);
+            process::exit(1);
+        }
+    }
+    for report in &reports {
+        for forfeit in &report.forfeits {
+            eprintln!("{} forfeited {}", report.name, forfeit);
+        }
+    }
+    exit_on_error(bench::report(
+        &reports,
+        &config.format,
+        config.cap,
+        stdout(),
+    ));
+}
+
+/// Exhaustive analysis
+///
+/// Play every deterministic AI strategy against every secret in a range &
+/// print exact results, e.g. `guessing_game analyze --min 0 --max 1000`
+fn analyze_main(config: AnalysisConfig) {
+    let analyses = analysis::run(&config);
+    exit_on_error(analysis::report(&analyses, &config, stdout()));
+}
+
+/// Print the high scores & player stats
+fn scores_main(config: ScoresConfig) {
+    exit_on_error(load_scoreboard(config.scores).render(stdout()));
+}
+
+/// Re-run recorded games
+///
+/// Play back every round recorded with `play --record`, e.g.
+/// `guessing_game replay rounds.log --speed 4`
+fn replay_main(config: ReplayConfig) {
+    let recordings = Recording::load_all(&config.path).unwrap_or_else(|err| {
+        eprintln!("Couldn't read recording {}: {}", config.path.display(), err);
+        process::exit(1);
+    });
+    exit_on_error(replay::replay(&recordings, config.speed, stdout()));
+}
+
+/// Multiplayer server
+///
+/// Host rounds for several players on a local port until killed, e.g.
+/// `guessing_game serve --port 7878`, then `nc localhost 7878` to join.
+fn serve_main(config: ServeConfig) {
+    let seed = resolve_seed(config.seed);
+    let settings = config
+        .settings
+        .unwrap_or_else(|| Difficulty::Normal.settings());
+    let rnd = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, seed);
+
+    let server = Server::bind(("127.0.0.1", config.port), settings, rnd).unwrap_or_else(|err| {
+        eprintln!("Couldn't listen on port {}: {}", config.port, err);
+        process::exit(1);
+    });
+    match server.local_addr() {
+        Ok(addr) => println!("Listening on {}, with secrets from seed {}", addr, seed),
+        Err(_) => println!("Listening, with secrets from seed {}", seed),
+    }
+    exit_on_error(server.run());
+}
+
+/// Exit with a non-zero code if writing a headless report failed, other than
+/// because the reader went away, e.g. when piped into `head`.
+fn exit_on_error(result: Result<()>) {
+    if let Err(err) = result {
+        if err.kind() == ErrorKind::BrokenPipe {
+            return;
         }
+        eprintln!("{}", err);
+        process::exit(1);
     }
 }
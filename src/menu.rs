@@ -1,44 +1,217 @@
-use std::io::{BufRead, Write};
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+};
 
 use crate::constants::*;
 use crate::io::{prompt, write, WriteArgs};
 
+/// Intro text shown above a menu's choices unless another is set.
+pub const DEFAULT_INTRO: &str = "Please choose from the following...";
+
+/// What a Menu should do after handling the user's choice: keep showing
+/// this menu, go back to the parent menu, or exit every menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    Stay,
+    Back,
+    Exit,
+}
+
+/// Types of Errors that can be returned when prompting with a menu. Invalid
+/// means the user's choice wasn't one of the options & holds the reason why,
+/// Io that reading or writing the menu's streams failed (including running
+/// out of input).
+#[derive(Debug)]
+pub enum MenuError {
+    Invalid(&'static str),
+    Io(io::Error),
+}
+
+impl From<io::Error> for MenuError {
+    fn from(err: io::Error) -> Self {
+        MenuError::Io(err)
+    }
+}
+
+impl fmt::Display for MenuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MenuError::Invalid(reason) => write!(f, "{}", reason),
+            MenuError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// A function run when the user chooses a menu option, given the menu's io
+/// streams & returning what the menu should do next, or Err if the streams
+/// fail.
+pub type Handler<'a, W, R> = Box<dyn FnMut(&mut W, &mut R) -> io::Result<MenuAction> + 'a>;
+
+enum Entry<'a, W, R> {
+    Option(Handler<'a, W, R>),
+    Submenu(Menu<'a, W, R>),
+    Back,
+}
+
+/// A menu of options, each with a handler run when the user chooses it.
+/// Options can also open nested submenus or go back to the parent menu.
+///
+/// # Example
+///
+/// let mut main_menu = Menu::new();
+/// main_menu.add_option("say hi", |writer, _| {
+///     write(writer, WriteArgs::Str("Hi!\n"))?;
+///     Ok(MenuAction::Stay)
+/// });
+/// main_menu.add_option("exit", |_, _| Ok(MenuAction::Exit));
+/// main_menu.run(&mut output, &mut input)?;
+pub struct Menu<'a, W, R> {
+    intro: String,
+    entries: Vec<(String, Entry<'a, W, R>)>,
+}
+
+impl<'a, W: Write, R: BufRead> Menu<'a, W, R> {
+    /// Create an empty Menu with the default intro text.
+    pub fn new() -> Self {
+        Menu {
+            intro: String::from(DEFAULT_INTRO),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Set the text shown above this menu's options.
+    pub fn set_intro(&mut self, intro: &str) -> &mut Self {
+        self.intro = String::from(intro);
+        self
+    }
+
+    /// Add an option with the given text, running the given handler when the
+    /// user chooses it.
+    pub fn add_option(
+        &mut self,
+        text: &str,
+        handler: impl FnMut(&mut W, &mut R) -> io::Result<MenuAction> + 'a,
+    ) -> &mut Self {
+        self.entries
+            .push((String::from(text), Entry::Option(Box::new(handler))));
+        self
+    }
+
+    /// Add an option with the given text that opens the given submenu when
+    /// chosen.
+    pub fn add_submenu(&mut self, text: &str, submenu: Menu<'a, W, R>) -> &mut Self {
+        self.entries
+            .push((String::from(text), Entry::Submenu(submenu)));
+        self
+    }
+
+    /// Add an option with the given text that goes back to the parent menu
+    /// when chosen.
+    pub fn add_back(&mut self, text: &str) -> &mut Self {
+        self.entries.push((String::from(text), Entry::Back));
+        self
+    }
+
+    /// Print the intro text & every option, numbered from 1.
+    pub fn render(&self, writer: &mut W) -> io::Result<()> {
+        let choices: Vec<&str> = self.entries.iter().map(|(text, _)| text.as_str()).collect();
+        render(&self.intro, &choices, writer)
+    }
+
+    /// Render the menu, prompt the user to choose an option, then handle
+    /// their choice. Returns Err if the choice isn't one of the options or
+    /// the streams fail.
+    pub fn prompt(&mut self, writer: &mut W, reader: &mut R) -> Result<MenuAction, MenuError> {
+        self.render(writer)?;
+        let choice = read_choice(self.entries.len(), &mut *writer, &mut *reader)?;
+
+        let action = match &mut self.entries[choice - 1].1 {
+            Entry::Option(handler) => handler(writer, reader)?,
+            Entry::Submenu(submenu) => match submenu.run(writer, reader)? {
+                // going back from a submenu lands on this menu again
+                MenuAction::Exit => MenuAction::Exit,
+                _ => MenuAction::Stay,
+            },
+            Entry::Back => MenuAction::Back,
+        };
+
+        Ok(action)
+    }
+
+    /// Prompt the user with this menu in a loop until they choose to go back
+    /// or exit, returning which one they chose. Returns Err if the streams
+    /// fail.
+    pub fn run(&mut self, writer: &mut W, reader: &mut R) -> io::Result<MenuAction> {
+        loop {
+            match self.prompt(writer, reader) {
+                Ok(MenuAction::Stay) => (),
+                Ok(action) => return Ok(action),
+                Err(MenuError::Invalid(reason)) => {
+                    write(&mut *writer, WriteArgs::Fmt(format_args!("{}\n", reason)))?
+                }
+                Err(MenuError::Io(err)) => return Err(err),
+            }
+        }
+    }
+}
+
+impl<W: Write, R: BufRead> Default for Menu<'_, W, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Take an array of strings and print them as choices in a menu. Then,
 /// prompt user to choose one of the choices by entering a number. Finally,
 /// return the value wrapped in a result.
 pub fn menu(
     choices: &[&str],
     mut writer: impl Write,
-    mut reader: impl BufRead,
-) -> Result<usize, &'static str> {
-    write(
-        &mut writer,
-        WriteArgs::Str("\nPlease choose from the following...\n"),
-    );
+    reader: impl BufRead,
+) -> Result<usize, MenuError> {
+    render(DEFAULT_INTRO, choices, &mut writer)?;
+    read_choice(choices.len(), writer, reader)
+}
+
+/// Print the given intro text followed by the given choices, numbered from 1.
+fn render(intro: &str, choices: &[&str], mut writer: impl Write) -> io::Result<()> {
+    write(&mut writer, WriteArgs::Fmt(format_args!("\n{}\n", intro)))?;
 
     for (index, choice) in choices.iter().enumerate() {
         write(
             &mut writer,
             WriteArgs::Fmt(format_args!("{}) {}\n", index + 1, choice)),
-        );
+        )?;
     }
 
-    let choice: Result<usize, _> = prompt(&mut writer, &mut reader).parse();
+    Ok(())
+}
+
+/// Prompt the user to enter the number of one of `count` choices.
+fn read_choice(
+    count: usize,
+    mut writer: impl Write,
+    mut reader: impl BufRead,
+) -> Result<usize, MenuError> {
+    let choice: Result<usize, _> = prompt(&mut writer, &mut reader)?.parse();
 
     if let Ok(num) = choice {
-        if num > 0 && num <= choices.len() {
+        if num > 0 && num <= count {
             Ok(num)
         } else {
-            Err(INVALID_CHOICE)
+            Err(MenuError::Invalid(INVALID_CHOICE))
         }
     } else {
-        Err(INVALID_CHOICE)
+        Err(MenuError::Invalid(INVALID_CHOICE))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::io::test_utils::{setup_io, setup_io_with_input};
+    use crate::io::test_utils::{
+        setup_io, setup_io_with_input, setup_io_with_many_inputs, TestReader,
+    };
 
     use super::*;
 
@@ -50,7 +223,7 @@ mod tests {
 
         assert!(writer
             .written_lines
-            .get(0)
+            .first()
             .unwrap()
             .contains("Please choose from the following..."));
     }
@@ -105,4 +278,106 @@ mod tests {
         let choices = ["choice"];
         menu(&choices, writer, reader).unwrap();
     }
+
+    #[test]
+    fn menu_struct_renders_intro_and_options() {
+        let (mut writer, _) = setup_io();
+        let mut test_menu: Menu<_, TestReader> = Menu::new();
+        test_menu
+            .set_intro("Pick one")
+            .add_option("first", |_, _| Ok(MenuAction::Exit))
+            .add_back("back");
+        test_menu.render(&mut writer).unwrap();
+
+        assert_eq!(writer.written_lines[0], "\nPick one\n");
+        assert!(writer.written_lines[1].contains("1) first"));
+        assert!(writer.written_lines[2].contains("2) back"));
+    }
+
+    #[test]
+    fn menu_struct_runs_handler_of_chosen_option() {
+        let (mut writer, mut reader) = setup_io_with_input("2");
+        let mut chosen = Vec::new();
+        let mut test_menu = Menu::new();
+        test_menu
+            .add_option("first", |_, _| Ok(MenuAction::Exit))
+            .add_option("second", |_, _| {
+                chosen.push("second");
+                Ok(MenuAction::Exit)
+            });
+
+        assert_eq!(
+            test_menu.run(&mut writer, &mut reader).unwrap(),
+            MenuAction::Exit
+        );
+        drop(test_menu);
+        assert_eq!(chosen, ["second"]);
+    }
+
+    #[test]
+    fn menu_struct_keeps_prompting_until_back_or_exit() {
+        let inputs = ["1", "1", "not a number", "2"];
+        let (mut writer, mut reader) = setup_io_with_many_inputs(&inputs);
+        let mut calls = 0;
+        let mut test_menu = Menu::new();
+        test_menu
+            .add_option("count", |_, _| {
+                calls += 1;
+                Ok(MenuAction::Stay)
+            })
+            .add_back("back");
+
+        assert_eq!(
+            test_menu.run(&mut writer, &mut reader).unwrap(),
+            MenuAction::Back
+        );
+        drop(test_menu);
+        assert_eq!(calls, 2);
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains(INVALID_CHOICE)));
+    }
+
+    #[test]
+    fn menu_struct_returns_to_parent_after_submenu_back() {
+        // open submenu, go back, then exit from the parent
+        let inputs = ["1", "1", "2"];
+        let (mut writer, mut reader) = setup_io_with_many_inputs(&inputs);
+        let mut submenu = Menu::new();
+        submenu.add_back("back");
+        let mut test_menu = Menu::new();
+        test_menu
+            .add_submenu("submenu", submenu)
+            .add_option("exit", |_, _| Ok(MenuAction::Exit));
+
+        assert_eq!(
+            test_menu.run(&mut writer, &mut reader).unwrap(),
+            MenuAction::Exit
+        );
+    }
+
+    #[test]
+    fn menu_struct_exits_every_menu_from_submenu() {
+        let inputs = ["1", "1"];
+        let (mut writer, mut reader) = setup_io_with_many_inputs(&inputs);
+        let mut submenu = Menu::new();
+        submenu.add_option("exit", |_, _| Ok(MenuAction::Exit));
+        let mut test_menu = Menu::new();
+        test_menu.add_submenu("submenu", submenu);
+
+        assert_eq!(
+            test_menu.run(&mut writer, &mut reader).unwrap(),
+            MenuAction::Exit
+        );
+    }
+
+    #[test]
+    fn menu_struct_returns_err_if_input_runs_out() {
+        let (mut writer, mut reader) = setup_io_with_many_inputs(&["not a number"]);
+        let mut test_menu = Menu::new();
+        test_menu.add_back("back");
+
+        assert!(test_menu.run(&mut writer, &mut reader).is_err());
+    }
 }
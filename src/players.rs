@@ -0,0 +1,333 @@
+use crate::{
+    feedback::{Direction, Feedback},
+    game::GameError,
+    guesser::Guesser,
+    random::{NumberGenerator, SecretSource},
+};
+
+/// The built-in AI strategies, used to pick a player by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Linear,
+    Binary,
+    Jump,
+    Random,
+}
+
+impl Strategy {
+    /// Every built-in strategy, in the order they should be reported.
+    pub const ALL: [Strategy; 4] = [
+        Strategy::Linear,
+        Strategy::Binary,
+        Strategy::Jump,
+        Strategy::Random,
+    ];
+
+    /// Short lowercase name used when reporting on the strategy.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Linear => "linear",
+            Strategy::Binary => "binary",
+            Strategy::Jump => "jump",
+            Strategy::Random => "random",
+        }
+    }
+
+    /// Whether a player using this strategy always makes the same guesses
+    /// against the same secret.
+    pub fn is_deterministic(&self) -> bool {
+        !matches!(self, Strategy::Random)
+    }
+
+    /// Create a fresh player using this strategy to search the inclusive
+    /// range [min, max].
+    pub fn player(&self, min: usize, max: usize) -> Box<dyn Guesser> {
+        match self {
+            Strategy::Linear => Box::new(LinearPlayer::new(min, max)),
+            Strategy::Binary => Box::new(BinaryPlayer::new(min, max)),
+            Strategy::Jump => Box::new(JumpPlayer::new(min, max)),
+            Strategy::Random => Box::new(RandomPlayer::new(min, max)),
+        }
+    }
+
+    /// Like `player`, but a player that makes random choices makes the same
+    /// ones every time it's given the same seed.
+    pub fn seeded_player(&self, min: usize, max: usize, seed: u64) -> Box<dyn Guesser> {
+        match self {
+            Strategy::Random => Box::new(RandomPlayer::with_seed(min, max, seed)),
+            _ => self.player(min, max),
+        }
+    }
+}
+
+/// AI player that guesses every number from the bottom of the range up,
+/// one at a time.
+pub struct LinearPlayer {
+    next: usize,
+}
+
+impl LinearPlayer {
+    /// Create a LinearPlayer searching the inclusive range [min, max].
+    pub fn new(min: usize, _max: usize) -> Self {
+        LinearPlayer { next: min }
+    }
+}
+
+impl Guesser for LinearPlayer {
+    fn guess(&mut self) -> Result<usize, GameError> {
+        Ok(self.next)
+    }
+
+    fn feedback(&mut self, feedback: Feedback) -> Result<(), GameError> {
+        let guess = feedback.guess;
+        if let Direction::TooLow = feedback.direction {
+            self.next = guess + 1;
+        }
+
+        Ok(())
+    }
+}
+
+/// AI player that always guesses the middle of the numbers it hasn't ruled
+/// out yet, halving the range with each guess.
+pub struct BinaryPlayer {
+    low: usize,
+    high: usize,
+}
+
+impl BinaryPlayer {
+    /// Create a BinaryPlayer searching the inclusive range [min, max].
+    pub fn new(min: usize, max: usize) -> Self {
+        BinaryPlayer {
+            low: min,
+            high: max,
+        }
+    }
+}
+
+impl Guesser for BinaryPlayer {
+    fn guess(&mut self) -> Result<usize, GameError> {
+        Ok(self.low + (self.high - self.low) / 2)
+    }
+
+    fn feedback(&mut self, feedback: Feedback) -> Result<(), GameError> {
+        let guess = feedback.guess;
+        match feedback.direction {
+            Direction::TooLow => self.low = guess + 1,
+            Direction::TooHigh => self.high = guess.saturating_sub(1).max(self.low),
+            Direction::Correct => (),
+        }
+
+        Ok(())
+    }
+}
+
+/// AI player that jumps through the range in blocks of sqrt(n) until it
+/// overshoots, then searches the last block linearly.
+pub struct JumpPlayer {
+    low: usize,
+    high: usize,
+    step: usize,
+    jumping: bool,
+}
+
+impl JumpPlayer {
+    /// Create a JumpPlayer searching the inclusive range [min, max].
+    pub fn new(min: usize, max: usize) -> Self {
+        let size = (max - min).saturating_add(1);
+        let step = ((size as f64).sqrt() as usize).max(1);
+
+        JumpPlayer {
+            low: min,
+            high: max,
+            step,
+            jumping: true,
+        }
+    }
+}
+
+impl Guesser for JumpPlayer {
+    fn guess(&mut self) -> Result<usize, GameError> {
+        if self.jumping {
+            Ok(self.low + (self.step - 1).min(self.high - self.low))
+        } else {
+            Ok(self.low)
+        }
+    }
+
+    fn feedback(&mut self, feedback: Feedback) -> Result<(), GameError> {
+        let guess = feedback.guess;
+        match feedback.direction {
+            Direction::TooLow => self.low = guess + 1,
+            Direction::TooHigh => {
+                self.high = guess.saturating_sub(1).max(self.low);
+                self.jumping = false;
+            }
+            Direction::Correct => (),
+        }
+
+        Ok(())
+    }
+}
+
+/// AI player that guesses a random number from those it hasn't ruled out
+/// yet.
+pub struct RandomPlayer {
+    low: usize,
+    high: usize,
+    rnd: NumberGenerator,
+}
+
+impl RandomPlayer {
+    /// Create a RandomPlayer searching the inclusive range [min, max].
+    pub fn new(min: usize, max: usize) -> Self {
+        Self::with_rng(min, max, NumberGenerator::new(min, max))
+    }
+
+    /// Create a RandomPlayer searching the inclusive range [min, max] that
+    /// always makes the same guesses for the same seed.
+    pub fn with_seed(min: usize, max: usize, seed: u64) -> Self {
+        Self::with_rng(min, max, NumberGenerator::with_seed(min, max, seed))
+    }
+
+    fn with_rng(min: usize, max: usize, rnd: NumberGenerator) -> Self {
+        RandomPlayer {
+            low: min,
+            high: max,
+            rnd,
+        }
+    }
+}
+
+impl Guesser for RandomPlayer {
+    fn guess(&mut self) -> Result<usize, GameError> {
+        Ok(self.rnd.gen_secret_in(self.low, self.high))
+    }
+
+    fn feedback(&mut self, feedback: Feedback) -> Result<(), GameError> {
+        let guess = feedback.guess;
+        match feedback.direction {
+            Direction::TooLow => self.low = guess + 1,
+            Direction::TooHigh => self.high = guess.saturating_sub(1).max(self.low),
+            Direction::Correct => (),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        constants::{MAX_SECRET, MIN_SECRET},
+        game::play_with,
+    };
+
+    use super::*;
+
+    /// Wraps a Guesser to record every guess it makes.
+    struct Recorder<G: Guesser> {
+        inner: G,
+        guesses: Vec<usize>,
+    }
+
+    impl<G: Guesser> Guesser for Recorder<G> {
+        fn guess(&mut self) -> Result<usize, GameError> {
+            let guess = self.inner.guess()?;
+            self.guesses.push(guess);
+            Ok(guess)
+        }
+
+        fn feedback(&mut self, feedback: Feedback) -> Result<(), GameError> {
+            self.inner.feedback(feedback)
+        }
+    }
+
+    /// Play the player returned by `new_player` against every possible
+    /// secret, asserting it wins within `max_guesses` & never guesses
+    /// outside the range.
+    fn assert_always_wins<G, F>(new_player: F, max_guesses: usize)
+    where
+        G: Guesser,
+        F: Fn(usize, usize) -> G,
+    {
+        for secret in MIN_SECRET..=MAX_SECRET {
+            let mut player = Recorder {
+                inner: new_player(MIN_SECRET, MAX_SECRET),
+                guesses: Vec::new(),
+            };
+            let guesses = play_with(secret, &mut player).unwrap();
+
+            assert!(
+                guesses <= max_guesses,
+                "took {guesses} guesses to find {secret}"
+            );
+            assert!(player
+                .guesses
+                .iter()
+                .all(|guess| (MIN_SECRET..=MAX_SECRET).contains(guess)));
+        }
+    }
+
+    #[test]
+    fn linear_player_always_wins_inside_range() {
+        assert_always_wins(LinearPlayer::new, MAX_SECRET - MIN_SECRET + 1)
+    }
+
+    #[test]
+    fn binary_player_always_wins_inside_range() {
+        assert_always_wins(BinaryPlayer::new, 7)
+    }
+
+    #[test]
+    fn jump_player_always_wins_inside_range() {
+        assert_always_wins(JumpPlayer::new, 20)
+    }
+
+    #[test]
+    fn jump_player_wins_at_top_of_range() {
+        let min = usize::MAX - 1000;
+        for secret in [min, usize::MAX - 500, usize::MAX - 1, usize::MAX] {
+            assert!(play_with(secret, &mut JumpPlayer::new(min, usize::MAX)).is_ok());
+        }
+    }
+
+    #[test]
+    fn random_player_always_wins_inside_range() {
+        assert_always_wins(RandomPlayer::new, MAX_SECRET - MIN_SECRET + 1)
+    }
+
+    #[test]
+    fn players_win_against_generated_secret() {
+        let secret = NumberGenerator::default().gen_secret();
+
+        assert!(play_with(secret, &mut LinearPlayer::new(MIN_SECRET, MAX_SECRET)).is_ok());
+        assert!(play_with(secret, &mut BinaryPlayer::new(MIN_SECRET, MAX_SECRET)).is_ok());
+        assert!(play_with(secret, &mut JumpPlayer::new(MIN_SECRET, MAX_SECRET)).is_ok());
+        assert!(play_with(secret, &mut RandomPlayer::new(MIN_SECRET, MAX_SECRET)).is_ok());
+    }
+
+    #[test]
+    fn strategy_players_always_win_inside_range() {
+        for strategy in Strategy::ALL {
+            assert_always_wins(
+                |min, max| strategy.player(min, max),
+                MAX_SECRET - MIN_SECRET + 1,
+            )
+        }
+    }
+
+    #[test]
+    fn seeded_random_players_make_same_guesses() {
+        let play = || {
+            let mut recorder = Recorder {
+                inner: RandomPlayer::with_seed(MIN_SECRET, MAX_SECRET, 7),
+                guesses: Vec::new(),
+            };
+            play_with(MAX_SECRET, &mut recorder).unwrap();
+            recorder.guesses
+        };
+
+        assert_eq!(play(), play());
+    }
+}
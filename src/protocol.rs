@@ -0,0 +1,503 @@
+use std::{
+    io::{BufRead, Result, Write},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    difficulty::{Difficulty, Settings},
+    game::{GameState, Rejection},
+    hints::{self, HintKind},
+    io::{write, WriteArgs},
+    json::{self, object, Value},
+    random::{NumberGenerator, SecretSource},
+    scores::Outcome,
+    scoring::Score,
+    summary::{Round, Summary},
+};
+
+/// The ways an interactive session can talk to whoever is playing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Interface {
+    /// Prompts & menus for a person at a terminal
+    #[default]
+    Text,
+    /// One JSON object per line, for other programs, see `run`
+    Jsonl,
+}
+
+impl Interface {
+    /// Parse an interface from its command line name.
+    pub fn parse(value: &str) -> std::result::Result<Self, String> {
+        match value {
+            "text" => Ok(Interface::Text),
+            "jsonl" => Ok(Interface::Jsonl),
+            other => Err(format!("Unknown protocol '{}'", other)),
+        }
+    }
+}
+
+/// Version of the protocol, sent in the `hello` event.
+pub const VERSION: u32 = 1;
+
+/// Every command the protocol accepts.
+pub const COMMANDS: [&str; 5] = ["new_game", "guess", "hint", "quit", "exit"];
+
+/// Run a session speaking the JSON Lines protocol on the given streams, for
+/// programs driving the game rather than people, until an `exit` command or
+/// the input runs out. Returns Err if either stream fails.
+///
+/// Every line read is a command object & every line written is an event
+/// object. The session starts with a `hello` event carrying the protocol
+/// VERSION, which changes whenever a command or event changes
+/// incompatibly; fields may be added within a version.
+///
+/// Commands, named by their `command` field:
+/// - `{"command":"new_game"}` starts a round, with the given settings if
+///   any, else at normal difficulty. A preset can be chosen with
+///   `"difficulty":"easy"`, or any of `min`, `max` & `max_guesses` given
+///   for a custom round.
+/// - `{"command":"guess","value":50}` guesses a number in the round's range.
+/// - `{"command":"hint","kind":"parity"}` takes a hint, see
+///   crate::hints::HintKind::name for the kinds.
+/// - `{"command":"quit"}` gives up the round.
+/// - `{"command":"exit"}` ends the session, as does closing the input.
+///
+/// Events, named by their `event` field:
+/// - `hello`: `version`, `seed`, `commands`
+/// - `round_started`: `round`, `difficulty`, `min`, `max`, `max_guesses`
+///   (null if unlimited)
+/// - `feedback`: `guess`, `direction` (`too_low` or `too_high`), `distance`
+///   (`hot`, `warm` or `cold`), `low` & `high` (where the secret must be),
+///   `guesses`, `guesses_left` (null if unlimited)
+/// - `hint`: `kind`, `text`, `cost`
+/// - `won`: `guesses`, `secret`, `score`, `elapsed_ms`
+/// - `lost`: after the last guess's `feedback`, with `guesses`, `secret`,
+///   `elapsed_ms`
+/// - `quit`: `guesses`, `secret`, `elapsed_ms`, also sent for a round left
+///   unfinished when the session ends
+/// - `error`: `message`, for a command that was malformed or not allowed,
+///   which is otherwise ignored
+/// - `goodbye`: `rounds`, `won`, `lost`, `quit`, sent last
+pub fn run<W: Write, R: BufRead>(
+    settings: Option<Settings>,
+    rnd: NumberGenerator,
+    mut output: W,
+    mut input: R,
+) -> Result<()> {
+    send(
+        &mut output,
+        object(vec![
+            ("event", "hello".into()),
+            ("version", VERSION.into()),
+            ("seed", rnd.seed().into()),
+            ("commands", COMMANDS.to_vec().into()),
+        ]),
+    )?;
+
+    let mut session = Session {
+        settings,
+        rnd,
+        round: None,
+        summary: Summary::new(),
+    };
+
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let command = json::parse(&line).map_err(|reason| format!("Invalid JSON: {}", reason));
+        let name = command
+            .as_ref()
+            .ok()
+            .and_then(|c| c.get("command"))
+            .and_then(Value::as_str);
+        if name == Some("exit") {
+            break;
+        }
+
+        let events = command.and_then(|command| session.handle(&command));
+        match events {
+            Ok(events) => {
+                for event in events {
+                    send(&mut output, event)?;
+                }
+            }
+            Err(message) => send(&mut output, error(message))?,
+        }
+    }
+
+    // a round left unfinished counts as quit
+    if let Some(event) = session.end_round(Outcome::Quit) {
+        send(&mut output, event)?;
+    }
+    let summary = &session.summary;
+    send(
+        &mut output,
+        object(vec![
+            ("event", "goodbye".into()),
+            ("rounds", summary.rounds().len().into()),
+            ("won", summary.count(Outcome::Won).into()),
+            ("lost", summary.count(Outcome::Lost).into()),
+            ("quit", summary.count(Outcome::Quit).into()),
+        ]),
+    )
+}
+
+/// Write an event as one line.
+fn send(output: impl Write, event: Value) -> Result<()> {
+    write(output, WriteArgs::Fmt(format_args!("{}\n", event)))
+}
+
+fn error(message: String) -> Value {
+    object(vec![("event", "error".into()), ("message", message.into())])
+}
+
+/// A round being played through the protocol.
+struct ActiveRound {
+    state: GameState,
+    started: Instant,
+}
+
+struct Session {
+    settings: Option<Settings>,
+    rnd: NumberGenerator,
+    round: Option<ActiveRound>,
+    summary: Summary,
+}
+
+impl Session {
+    /// Carry out a command, returning the events it caused, or Err with
+    /// why it couldn't be.
+    fn handle(&mut self, command: &Value) -> std::result::Result<Vec<Value>, String> {
+        let name = match command.get("command") {
+            Some(Value::String(name)) => name.as_str(),
+            Some(_) => return Err(String::from("'command' must be a string")),
+            None => return Err(String::from("Commands need a 'command' field")),
+        };
+
+        match name {
+            "new_game" => self.new_game(command).map(|event| vec![event]),
+            "guess" => self.guess(command),
+            "hint" => self.hint(command).map(|event| vec![event]),
+            "quit" => self
+                .end_round(Outcome::Quit)
+                .map(|event| vec![event])
+                .ok_or_else(no_round),
+            other => Err(format!("Unknown command '{}'", other)),
+        }
+    }
+
+    fn new_game(&mut self, command: &Value) -> std::result::Result<Value, String> {
+        if self.round.is_some() {
+            return Err(String::from(
+                "A round is already being played, quit it first",
+            ));
+        }
+
+        let preset = match command.get("difficulty") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(name)) => Some(
+                Difficulty::PRESETS
+                    .into_iter()
+                    .find(|d| d.name() == name)
+                    .ok_or_else(|| format!("There's no '{}' difficulty", name))?,
+            ),
+            Some(_) => return Err(String::from("'difficulty' must be a string")),
+        };
+        let (min, max) = (number(command, "min")?, number(command, "max")?);
+        let max_guesses = number(command, "max_guesses")?;
+
+        let difficulty = match (preset, self.settings) {
+            (Some(_), _) if min.is_some() || max.is_some() || max_guesses.is_some() => {
+                return Err(String::from(
+                    "Give either a difficulty or a range & guess limit, not both",
+                ))
+            }
+            (Some(preset), _) => preset,
+            (None, _) if min.is_some() || max.is_some() || max_guesses.is_some() => {
+                let default = self.settings.unwrap_or_default();
+                Difficulty::Custom(Settings {
+                    min: min.unwrap_or(default.min),
+                    max: max.unwrap_or(default.max),
+                    max_guesses: max_guesses.or(default.max_guesses),
+                })
+            }
+            (None, Some(settings)) => Difficulty::Custom(settings),
+            (None, None) => Difficulty::Normal,
+        };
+        let settings = difficulty.settings();
+        if settings.min > settings.max {
+            return Err(format!(
+                "min ({}) must not be greater than max ({})",
+                settings.min, settings.max
+            ));
+        }
+        if settings.max_guesses == Some(0) {
+            return Err(String::from("max_guesses must be at least 1"));
+        }
+
+        let secret = self.rnd.gen_secret_in(settings.min, settings.max);
+        self.round = Some(ActiveRound {
+            state: GameState::new(secret, settings),
+            started: Instant::now(),
+        });
+
+        Ok(object(vec![
+            ("event", "round_started".into()),
+            ("round", (self.summary.rounds().len() + 1).into()),
+            ("difficulty", difficulty.name().into()),
+            ("min", settings.min.into()),
+            ("max", settings.max.into()),
+            ("max_guesses", settings.max_guesses.into()),
+        ]))
+    }
+
+    fn guess(&mut self, command: &Value) -> std::result::Result<Vec<Value>, String> {
+        let round = self.round.as_mut().ok_or_else(no_round)?;
+        let guess = number(command, "value")?
+            .ok_or_else(|| String::from("Guesses need a 'value' field, a non-negative integer"))?;
+
+        let settings = round.state.settings();
+        round
+            .state
+            .check(guess)
+            .map_err(|rejection| match rejection {
+                Rejection::OutOfRange => format!(
+                    "{} is outside the round's range [{}, {}]",
+                    guess, settings.min, settings.max
+                ),
+                Rejection::Repeated => format!("{} was already guessed", guess),
+            })?;
+
+        let feedback = round.state.guess(guess);
+        if feedback.is_correct() {
+            let event = self.end_round(Outcome::Won);
+            return Ok(event.into_iter().collect());
+        }
+
+        let (low, high) = round.state.interval();
+        let mut events = vec![object(vec![
+            ("event", "feedback".into()),
+            ("guess", guess.into()),
+            ("direction", feedback.direction.name().into()),
+            ("distance", feedback.distance.map(|d| d.name()).into()),
+            ("low", low.into()),
+            ("high", high.into()),
+            ("guesses", round.state.guesses().into()),
+            ("guesses_left", round.state.guesses_left().into()),
+        ])];
+        if round.state.is_over() {
+            events.extend(self.end_round(Outcome::Lost));
+        }
+
+        Ok(events)
+    }
+
+    fn hint(&mut self, command: &Value) -> std::result::Result<Value, String> {
+        let round = self.round.as_mut().ok_or_else(no_round)?;
+        let kind = match command.get("kind") {
+            Some(Value::String(name)) => {
+                HintKind::parse(name).ok_or_else(|| format!("There's no '{}' hint", name))?
+            }
+            _ => return Err(String::from("Hints need a 'kind' field, e.g. \"parity\"")),
+        };
+
+        let hint = round.state.hint(kind);
+        Ok(object(vec![
+            ("event", "hint".into()),
+            ("kind", kind.name().into()),
+            ("text", hints::render(&hint).into()),
+            ("cost", kind.cost().into()),
+        ]))
+    }
+
+    /// End the round being played, if any, adding it to the summary &
+    /// returning the event announcing how it ended.
+    fn end_round(&mut self, outcome: Outcome) -> Option<Value> {
+        let round = self.round.take()?;
+        let state = round.state;
+        let duration = round.started.elapsed();
+        self.summary.add(Round {
+            settings: state.settings(),
+            secret: state.secret(),
+            guesses: state.guesses(),
+            duration,
+            outcome,
+        });
+
+        let mut fields = vec![
+            ("event", outcome.name().into()),
+            ("guesses", state.guesses().into()),
+            ("secret", state.secret().into()),
+        ];
+        if outcome == Outcome::Won {
+            fields.push(("score", Score::new(&state, duration).total.into()));
+        }
+        fields.push(("elapsed_ms", millis(duration).into()));
+
+        Some(object(fields))
+    }
+}
+
+fn no_round() -> String {
+    String::from("No round is being played, start one with new_game")
+}
+
+/// The non-negative integer in the given field of a command, or None if it
+/// isn't given.
+fn number(command: &Value, field: &str) -> std::result::Result<Option<usize>, String> {
+    match command.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_usize()
+            .map(Some)
+            .ok_or_else(|| format!("'{}' must be a non-negative integer", field)),
+    }
+}
+
+fn millis(duration: Duration) -> u128 {
+    duration.as_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::test_utils::setup_io_with_many_inputs;
+
+    use super::*;
+
+    /// Run a session with the given command lines & return every event
+    /// sent, parsed back from JSON.
+    fn events(commands: &[&str]) -> Vec<Value> {
+        let (mut writer, reader) = setup_io_with_many_inputs(commands);
+        let rnd = NumberGenerator::with_seed(0, 100, 1);
+        run(None, rnd, &mut writer, reader).unwrap();
+
+        writer
+            .written_lines
+            .iter()
+            .map(|line| {
+                assert!(line.ends_with('\n') && line.matches('\n').count() == 1);
+                json::parse(line).unwrap()
+            })
+            .collect()
+    }
+
+    fn field<'a>(event: &'a Value, name: &str) -> &'a Value {
+        event.get(name).unwrap()
+    }
+
+    fn name(event: &Value) -> &str {
+        field(event, "event").as_str().unwrap()
+    }
+
+    #[test]
+    fn binary_search_wins_a_round_through_the_protocol() {
+        let secret = NumberGenerator::with_seed(0, 100, 1).gen_secret_in(0, 100);
+        // work out the guesses a binary search makes for the secret
+        let mut state = GameState::new(secret, Difficulty::Normal.settings());
+        let mut guesses = Vec::new();
+        while !state.is_won() {
+            let (low, high) = state.interval();
+            guesses.push(low + (high - low) / 2);
+            state.guess(low + (high - low) / 2);
+        }
+
+        let mut commands = vec![String::from(r#"{"command":"new_game"}"#)];
+        commands.extend(
+            guesses
+                .iter()
+                .map(|g| format!(r#"{{"command":"guess","value":{}}}"#, g)),
+        );
+        // nothing after exit is read
+        commands.push(String::from(r#"{"command":"exit"}"#));
+        commands.push(String::from(r#"{"command":"new_game"}"#));
+        let commands: Vec<&str> = commands.iter().map(String::as_str).collect();
+        let events = events(&commands);
+
+        let names: Vec<&str> = events.iter().map(name).collect();
+        let mut expected = vec!["hello", "round_started"];
+        expected.extend(vec!["feedback"; guesses.len() - 1]);
+        expected.extend(["won", "goodbye"]);
+        assert_eq!(names, expected);
+        assert_eq!(field(&events[0], "version"), &Value::from(VERSION));
+        assert_eq!(field(&events[1], "max_guesses"), &Value::from(10usize));
+        let won = &events[names.len() - 2];
+        assert_eq!(field(won, "secret"), &Value::from(secret));
+        assert_eq!(field(won, "guesses"), &Value::from(guesses.len()));
+        assert_eq!(field(&events[names.len() - 1], "won"), &Value::from(1usize));
+    }
+
+    #[test]
+    fn feedback_narrows_the_interval_and_losing_ends_the_round() {
+        let events = events(&[
+            r#"{"command":"new_game","min":0,"max":100,"max_guesses":1}"#,
+            r#"{"command":"guess","value":101}"#,
+            r#"{"command":"guess","value":0}"#,
+            r#"{"command":"guess","value":1}"#,
+        ]);
+        let names: Vec<&str> = events.iter().map(name).collect();
+
+        // the secret from seed 1 isn't 0
+        assert_eq!(
+            names,
+            [
+                "hello",
+                "round_started",
+                "error",
+                "feedback",
+                "lost",
+                "error",
+                "goodbye"
+            ]
+        );
+        assert_eq!(field(&events[1], "difficulty").as_str(), Some("custom"));
+        assert_eq!(field(&events[3], "direction").as_str(), Some("too_low"));
+        assert_eq!(field(&events[3], "low"), &Value::from(1usize));
+        assert_eq!(field(&events[3], "guesses_left"), &Value::from(0usize));
+        assert_eq!(field(&events[6], "lost"), &Value::from(1usize));
+    }
+
+    #[test]
+    fn malformed_commands_get_errors_and_change_nothing() {
+        let events = events(&[
+            "not json",
+            r#"{"value":3}"#,
+            r#"{"command":"dance"}"#,
+            r#"{"command":"guess","value":3}"#,
+            r#"{"command":"new_game","difficulty":"easy"}"#,
+            r#"{"command":"new_game"}"#,
+            r#"{"command":"guess","value":-3}"#,
+            r#"{"command":"hint","kind":"parity"}"#,
+            r#"{"command":"quit"}"#,
+        ]);
+        let names: Vec<&str> = events.iter().map(name).collect();
+
+        assert_eq!(
+            names,
+            [
+                "hello",
+                "error",
+                "error",
+                "error",
+                "error",
+                "round_started",
+                "error",
+                "error",
+                "hint",
+                "quit",
+                "goodbye"
+            ]
+        );
+        assert!(field(&events[1], "message")
+            .as_str()
+            .unwrap()
+            .starts_with("Invalid JSON"));
+        assert_eq!(field(&events[8], "cost"), &Value::from(50u32));
+        assert_eq!(field(&events[10], "quit"), &Value::from(1usize));
+    }
+}
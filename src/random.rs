@@ -1,39 +1,68 @@
-use rand::{rngs::ThreadRng, Rng};
+use std::env;
+
+use rand::{distributions::Uniform, rngs::StdRng, Rng, SeedableRng};
 
 use crate::constants::{MAX_SECRET, MIN_SECRET};
 
+/// Environment variable that sets the seed secrets are generated from when
+/// no `--seed` is given.
+pub const SEED_ENV: &str = "GUESSING_GAME_SEED";
+
+/// Anything games can draw their secrets from.
+pub trait SecretSource {
+    /// Pick a secret belonging to the inclusive range [min, max]
+    fn gen_secret_in(&mut self, min: usize, max: usize) -> usize;
+}
+
 pub struct NumberGenerator {
-    thread_rng: Option<ThreadRng>,
+    rng: StdRng,
+    seed: u64,
     max: usize,
     min: usize,
 }
 
 impl NumberGenerator {
-    /// Create a number generator based on ThreadRng & gen_range with given
-    /// min & max values
+    /// Create a number generator with given min & max values, seeded with a
+    /// random seed
     pub fn new(min: usize, max: usize) -> Self {
+        Self::with_seed(min, max, rand::random())
+    }
+
+    /// Create a number generator with given min & max values that always
+    /// generates the same numbers for the same seed
+    pub fn with_seed(min: usize, max: usize, seed: u64) -> Self {
         NumberGenerator {
-            thread_rng: None,
+            rng: StdRng::seed_from_u64(seed),
+            seed,
             max,
             min,
         }
     }
 
+    /// The seed this generator was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
     /// Generate a secret number
     pub fn gen_secret(&mut self) -> usize {
-        self.get_rng().gen_range(&self.min, &self.max)
+        self.rng.gen_range(self.min, self.max)
     }
 
-    fn get_rng(&mut self) -> ThreadRng {
-        match self.thread_rng {
-            Some(instance) => instance,
-            None => self.init_rng(),
-        }
+    /// Generate a seed for another generator, so everything random in a run
+    /// can be reproduced from this generator's seed
+    pub fn gen_seed(&mut self) -> u64 {
+        self.rng.gen()
     }
+}
 
-    fn init_rng(&mut self) -> ThreadRng {
-        self.thread_rng = Some(rand::thread_rng());
-        self.get_rng()
+impl SecretSource for NumberGenerator {
+    /// Generate a secret number belonging to the inclusive range [min, max],
+    /// ignoring this generator's own min & max values
+    fn gen_secret_in(&mut self, min: usize, max: usize) -> usize {
+        // sample the inclusive range directly, as max + 1 overflows when
+        // max is usize::MAX
+        self.rng.sample(Uniform::new_inclusive(min, max))
     }
 }
 
@@ -44,13 +73,96 @@ impl Default for NumberGenerator {
     }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//
-//     #[test]
-//     fn gen_secret_returns_a_number_between_min_and_max() {
-//         // TODO: finish this test?
-//         assert!(false)
-//     }
-// }
+/// A SecretSource that hands out a scripted sequence of secrets in order,
+/// starting over once it runs out. Secrets outside the requested range are
+/// clamped into it.
+pub struct ScriptedSecrets {
+    secrets: Vec<usize>,
+    next: usize,
+}
+
+impl ScriptedSecrets {
+    /// Create a source handing out the given secrets. Panics if there are
+    /// none.
+    pub fn new(secrets: Vec<usize>) -> Self {
+        assert!(!secrets.is_empty(), "ScriptedSecrets needs a secret");
+        ScriptedSecrets { secrets, next: 0 }
+    }
+
+    /// Create a source that always hands out the same secret.
+    pub fn fixed(secret: usize) -> Self {
+        Self::new(vec![secret])
+    }
+}
+
+impl SecretSource for ScriptedSecrets {
+    fn gen_secret_in(&mut self, min: usize, max: usize) -> usize {
+        let secret = self.secrets[self.next];
+        self.next = (self.next + 1) % self.secrets.len();
+        secret.clamp(min, max)
+    }
+}
+
+/// Parse a seed given on the command line or in the environment.
+pub fn parse_seed(value: &str) -> Result<u64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Seed must be a non-negative integer, not '{}'", value))
+}
+
+/// The seed set in the SEED_ENV environment variable, if any.
+pub fn seed_from_env() -> Result<Option<u64>, String> {
+    match env::var(SEED_ENV) {
+        Ok(value) if !value.is_empty() => parse_seed(&value).map(Some),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gen_secret_returns_a_number_between_min_and_max() {
+        let mut rnd = NumberGenerator::new(3, 6);
+
+        for _ in 0..100 {
+            assert!((3..6).contains(&rnd.gen_secret()));
+            assert!((10..=12).contains(&rnd.gen_secret_in(10, 12)));
+        }
+    }
+
+    #[test]
+    fn gen_secret_in_reaches_usize_max() {
+        let mut rnd = NumberGenerator::default();
+
+        assert_eq!(rnd.gen_secret_in(usize::MAX, usize::MAX), usize::MAX);
+        assert!(rnd.gen_secret_in(usize::MAX - 1, usize::MAX) >= usize::MAX - 1);
+        rnd.gen_secret_in(0, usize::MAX);
+    }
+
+    #[test]
+    fn same_seed_generates_same_secrets() {
+        let mut first = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, 42);
+        let mut second = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, 42);
+        let first: Vec<usize> = (0..20).map(|_| first.gen_secret_in(0, 1000)).collect();
+        let second: Vec<usize> = (0..20).map(|_| second.gen_secret_in(0, 1000)).collect();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn scripted_secrets_repeat_in_order_and_stay_in_range() {
+        let mut secrets = ScriptedSecrets::new(vec![5, 50, 500]);
+        let drawn: Vec<usize> = (0..4).map(|_| secrets.gen_secret_in(0, 100)).collect();
+
+        assert_eq!(drawn, vec![5, 50, 100, 5]);
+    }
+
+    #[test]
+    fn parse_seed_rejects_non_numbers() {
+        assert_eq!(parse_seed("1234"), Ok(1234));
+        assert!(parse_seed("-1").is_err());
+        assert!(parse_seed("abc").is_err());
+    }
+}
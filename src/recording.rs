@@ -0,0 +1,282 @@
+use std::{
+    fs,
+    io::{self, ErrorKind, Write},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{
+    difficulty::{Difficulty, Settings},
+    feedback::{Direction, Distance, Feedback},
+    game::GameState,
+    hints::HintKind,
+    scores::Outcome,
+};
+
+/// Something the player did during a recorded round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    /// A guess made `at` into the round, along with the Feedback given
+    Guess {
+        at: Duration,
+        feedback: Feedback,
+    },
+    Hint(HintKind),
+}
+
+/// A log of one round, detailed enough to play it again exactly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+    /// Seed the session's secrets came from
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub secret: usize,
+    /// Every guess & hint, in the order they were made
+    pub moves: Vec<Move>,
+    /// How the round ended, or None if it was saved to finish later
+    pub outcome: Option<Outcome>,
+    /// Time spent playing the round
+    pub duration: Duration,
+}
+
+impl Recording {
+    /// Record a round from its final state, given the time into the round
+    /// each guess was made, see crate::game::Game::times.
+    pub fn new(
+        seed: u64,
+        difficulty: Difficulty,
+        state: &GameState,
+        times: &[Duration],
+        outcome: Option<Outcome>,
+        duration: Duration,
+    ) -> Self {
+        let mut moves = Vec::new();
+        let mut hints = state.hints().iter().peekable();
+        for (i, (feedback, at)) in state.history().iter().zip(times).enumerate() {
+            while let Some(taken) = hints.next_if(|h| h.after == i) {
+                moves.push(Move::Hint(taken.kind));
+            }
+            moves.push(Move::Guess {
+                at: *at,
+                feedback: *feedback,
+            });
+        }
+        moves.extend(hints.map(|taken| Move::Hint(taken.kind)));
+
+        Recording {
+            seed,
+            difficulty,
+            secret: state.secret(),
+            moves,
+            outcome,
+            duration,
+        }
+    }
+
+    /// The Feedback for every recorded guess, oldest first.
+    pub fn feedback(&self) -> Vec<Feedback> {
+        self.moves
+            .iter()
+            .filter_map(|m| match m {
+                Move::Guess { feedback, .. } => Some(*feedback),
+                Move::Hint(_) => None,
+            })
+            .collect()
+    }
+
+    /// Add the round to the end of the recording file at the given path,
+    /// which can hold any number of rounds.
+    pub fn append(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        file.write_all(self.to_lines().as_bytes())
+    }
+
+    /// Load every round recorded in the file at the given path.
+    pub fn load_all(path: impl AsRef<Path>) -> io::Result<Vec<Self>> {
+        Self::parse_all(&fs::read_to_string(path)?)
+            .map_err(|reason| io::Error::new(ErrorKind::InvalidData, reason))
+    }
+
+    /// Format the round as tab separated lines: a `round` line with the
+    /// seed, difficulty, range, guess limit & secret, a line for each guess
+    /// & hint, then an `end` line with the time spent & outcome.
+    fn to_lines(&self) -> String {
+        let settings = self.difficulty.settings();
+        let mut lines = vec![format!(
+            "round\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.seed,
+            self.difficulty.name(),
+            settings.min,
+            settings.max,
+            optional(settings.max_guesses.map(|limit| limit.to_string())),
+            self.secret,
+        )];
+
+        for m in &self.moves {
+            lines.push(match m {
+                Move::Guess { at, feedback } => format!(
+                    "guess\t{}\t{}\t{}\t{}",
+                    at.as_millis(),
+                    feedback.guess,
+                    feedback.direction.name(),
+                    optional(feedback.distance.map(|d| d.name().to_string())),
+                ),
+                Move::Hint(kind) => format!("hint\t{}", kind.name()),
+            });
+        }
+
+        let outcome = self.outcome.map_or("saved", |outcome| outcome.name());
+        lines.push(format!("end\t{}\t{}", self.duration.as_millis(), outcome));
+
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    /// Parse every round in a recording file.
+    fn parse_all(contents: &str) -> Result<Vec<Self>, String> {
+        let mut recordings = Vec::new();
+        let mut current: Option<Recording> = None;
+
+        for (i, line) in contents.lines().enumerate() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let malformed = || format!("Line {} of the recording is malformed", i + 1);
+
+            match (fields.as_slice(), current.as_mut()) {
+                (["round", seed, name, min, max, limit, secret], None) => {
+                    let settings = Settings {
+                        min: number(min).ok_or_else(malformed)?,
+                        max: number(max).ok_or_else(malformed)?,
+                        max_guesses: match *limit {
+                            "-" => None,
+                            limit => Some(number(limit).ok_or_else(malformed)?),
+                        },
+                    };
+                    current = Some(Recording {
+                        seed: seed.parse().map_err(|_| malformed())?,
+                        difficulty: Difficulty::from_parts(name, settings),
+                        secret: number(secret).ok_or_else(malformed)?,
+                        moves: Vec::new(),
+                        outcome: None,
+                        duration: Duration::ZERO,
+                    });
+                }
+                (["guess", at, guess, direction, distance], Some(recording)) => {
+                    let feedback = Feedback {
+                        guess: number(guess).ok_or_else(malformed)?,
+                        direction: Direction::parse(direction).ok_or_else(malformed)?,
+                        distance: match *distance {
+                            "-" => None,
+                            distance => Some(Distance::parse(distance).ok_or_else(malformed)?),
+                        },
+                    };
+                    recording.moves.push(Move::Guess {
+                        at: millis(at).ok_or_else(malformed)?,
+                        feedback,
+                    });
+                }
+                (["hint", kind], Some(recording)) => {
+                    recording
+                        .moves
+                        .push(Move::Hint(HintKind::parse(kind).ok_or_else(malformed)?));
+                }
+                (["end", duration, outcome], Some(recording)) => {
+                    recording.duration = millis(duration).ok_or_else(malformed)?;
+                    recording.outcome = match *outcome {
+                        "saved" => None,
+                        outcome => Some(Outcome::parse(outcome).ok_or_else(malformed)?),
+                    };
+                    recordings.extend(current.take());
+                }
+                _ => return Err(malformed()),
+            }
+        }
+
+        match current {
+            Some(_) => Err(String::from("The recording ends part way through a round")),
+            None => Ok(recordings),
+        }
+    }
+}
+
+fn optional(value: Option<String>) -> String {
+    value.unwrap_or_else(|| String::from("-"))
+}
+
+fn number(value: &str) -> Option<usize> {
+    value.parse().ok()
+}
+
+fn millis(value: &str) -> Option<Duration> {
+    value.parse().ok().map(Duration::from_millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn recording() -> Recording {
+        let mut state = GameState::new(37, Difficulty::Normal.settings());
+        state.guess(50);
+        state.hint(HintKind::Parity);
+        state.guess(25);
+        state.guess(37);
+        let times = [500, 1500, 4000].map(Duration::from_millis);
+
+        Recording::new(
+            7,
+            Difficulty::Normal,
+            &state,
+            &times,
+            Some(Outcome::Won),
+            Duration::from_millis(4200),
+        )
+    }
+
+    #[test]
+    fn moves_keep_the_order_guesses_and_hints_were_made_in() {
+        let moves = recording().moves;
+
+        assert_eq!(moves.len(), 4);
+        assert_eq!(moves[1], Move::Hint(HintKind::Parity));
+        assert!(matches!(moves[2], Move::Guess { feedback, .. } if feedback.guess == 25));
+    }
+
+    #[test]
+    fn rounds_appended_to_a_file_load_again() {
+        let path = env::temp_dir()
+            .join(format!(
+                "guessing_game_recording_test_{}",
+                std::process::id()
+            ))
+            .join("rounds.log");
+        let _ = fs::remove_file(&path);
+        let won = recording();
+        let saved = Recording {
+            outcome: None,
+            ..recording()
+        };
+        won.append(&path).unwrap();
+        saved.append(&path).unwrap();
+
+        assert_eq!(Recording::load_all(&path).unwrap(), vec![won, saved]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn unfinished_recordings_are_rejected() {
+        let lines = recording().to_lines();
+        let unfinished = lines.replace("end\t4200\twon\n", "");
+
+        assert!(Recording::parse_all(&unfinished).is_err());
+        assert!(Recording::parse_all("guess\t1\t2\tlow\t-\n").is_err());
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fmt::Arguments,
+    io::{BufRead, Read, Result, Write},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    feedback::render,
+    game::{Game, GameError},
+    io::{is_eof, write, WriteArgs},
+    recording::{Move, Recording},
+    scores::Outcome,
+};
+
+/// Play back recorded rounds on the write stream, by feeding each round's
+/// guesses & hints to a Game as if a human were typing them, so the replay
+/// shows exactly what the player saw. Inputs are spaced out as they were
+/// recorded, sped up by `speed` times, or not at all if it's None. Tells
+/// the viewer if a round plays out differently than it was recorded, e.g.
+/// because the rules changed since.
+pub fn replay<W: Write>(recordings: &[Recording], speed: Option<f64>, output: W) -> Result<()> {
+    let output = RefCell::new(output);
+
+    for (i, recording) in recordings.iter().enumerate() {
+        write(
+            Shared(&output),
+            WriteArgs::Fmt(format_args!(
+                "\nReplaying round {} of {}: {} (seed {}, secret {})\n",
+                i + 1,
+                recordings.len(),
+                recording.difficulty.describe(),
+                recording.seed,
+                recording.secret
+            )),
+        )?;
+
+        let reader = ReplayReader::new(recording, speed, Shared(&output));
+        let settings = recording.difficulty.settings();
+        let mut game = Game::with_settings(recording.secret, settings, Shared(&output), reader);
+        let outcome = match game.play() {
+            Ok(()) => Some(Outcome::Won),
+            Err(GameError::OutOfGuesses) => Some(Outcome::Lost),
+            Err(GameError::Saved) => None,
+            // the recording ran out before the round ended
+            Err(GameError::Io(err)) if is_eof(&err) => None,
+            Err(GameError::Io(err)) => return Err(err),
+            Err(_) => Some(Outcome::Quit),
+        };
+        let replayed: Vec<_> = game.state().history().to_vec();
+        let recorded = recording.feedback();
+
+        let diverged = replayed
+            .iter()
+            .zip(&recorded)
+            .position(|(replayed, recorded)| replayed != recorded);
+        match diverged {
+            Some(i) => write(
+                Shared(&output),
+                WriteArgs::Fmt(format_args!(
+                    "\nThe replay diverged from the recording at guess {}: recorded '{}', replayed '{}'.\n",
+                    i + 1,
+                    render(&recorded[i]),
+                    render(&replayed[i])
+                )),
+            )?,
+            None if replayed.len() != recorded.len() || outcome != recording.outcome => write(
+                Shared(&output),
+                WriteArgs::Fmt(format_args!(
+                    "\nThe replay diverged from the recording: recorded {} after {} guesses, replayed {} after {}.\n",
+                    describe(recording.outcome),
+                    recorded.len(),
+                    describe(outcome),
+                    replayed.len()
+                )),
+            )?,
+            None => write(
+                Shared(&output),
+                WriteArgs::Fmt(format_args!(
+                    "\nReplayed as recorded: {} after {} guesses in {:.1}s.\n",
+                    describe(outcome),
+                    replayed.len(),
+                    recording.duration.as_secs_f64()
+                )),
+            )?,
+        }
+    }
+
+    Ok(())
+}
+
+fn describe(outcome: Option<Outcome>) -> &'static str {
+    outcome.map_or("saved", |outcome| outcome.name())
+}
+
+/// A handle on a write stream shared between a Game & the ReplayReader
+/// feeding it.
+struct Shared<'a, W>(&'a RefCell<W>);
+
+impl<W: Write> Write for Shared<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn write_fmt(&mut self, args: Arguments<'_>) -> Result<()> {
+        self.0.borrow_mut().write_fmt(args)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.0.borrow_mut().flush()
+    }
+}
+
+/// A read stream that types out a recorded round's inputs, echoing each to
+/// the write stream as a terminal would, after waiting until the time it
+/// was recorded at.
+struct ReplayReader<W> {
+    /// Each line of input, along with when it was entered if recorded
+    inputs: VecDeque<(Option<Duration>, String)>,
+    speed: Option<f64>,
+    /// When the last input was entered
+    clock: Duration,
+    line: Vec<u8>,
+    read: usize,
+    echo: W,
+}
+
+impl<W: Write> ReplayReader<W> {
+    fn new(recording: &Recording, speed: Option<f64>, echo: W) -> Self {
+        let mut inputs: VecDeque<_> = recording
+            .moves
+            .iter()
+            .map(|m| match m {
+                Move::Guess { at, feedback } => (Some(*at), feedback.guess.to_string()),
+                Move::Hint(kind) => (None, format!("/hint {}", kind.name())),
+            })
+            .collect();
+        match recording.outcome {
+            Some(Outcome::Quit) => inputs.push_back((Some(recording.duration), "quit".into())),
+            None => inputs.push_back((Some(recording.duration), "/save".into())),
+            Some(_) => (),
+        }
+
+        ReplayReader {
+            inputs,
+            speed,
+            clock: Duration::ZERO,
+            line: Vec::new(),
+            read: 0,
+            echo,
+        }
+    }
+}
+
+impl<W: Write> Read for ReplayReader<W> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let available = self.fill_buf()?;
+        let count = available.len().min(buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.consume(count);
+        Ok(count)
+    }
+}
+
+impl<W: Write> BufRead for ReplayReader<W> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        if self.read == self.line.len() {
+            if let Some((at, input)) = self.inputs.pop_front() {
+                if let (Some(at), Some(speed)) = (at, self.speed) {
+                    thread::sleep(at.saturating_sub(self.clock).div_f64(speed));
+                    self.clock = at;
+                }
+                write(&mut self.echo, WriteArgs::Fmt(format_args!("{}\n", input)))?;
+                self.line = format!("{}\n", input).into_bytes();
+                self.read = 0;
+            }
+        }
+
+        Ok(&self.line[self.read..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.read += amt;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        difficulty::Difficulty, feedback::Direction, game::GameState, hints::HintKind,
+        io::test_utils::setup_io,
+    };
+
+    use super::*;
+
+    fn recording(outcome: Option<Outcome>) -> Recording {
+        let mut state = GameState::new(37, Difficulty::Normal.settings());
+        state.guess(50);
+        state.hint(HintKind::Parity);
+        state.guess(25);
+        let times = [500, 1500].map(Duration::from_millis);
+
+        Recording::new(
+            7,
+            Difficulty::Normal,
+            &state,
+            &times,
+            outcome,
+            Duration::from_millis(2000),
+        )
+    }
+
+    fn replayed(recordings: &[Recording]) -> Vec<String> {
+        let (mut writer, _) = setup_io();
+        replay(recordings, None, &mut writer).unwrap();
+        writer.written_lines
+    }
+
+    #[test]
+    fn replay_shows_the_round_as_played() {
+        let lines = replayed(&[recording(Some(Outcome::Quit))]);
+
+        assert!(lines.iter().any(|line| line == "/hint parity\n"));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("The secret is odd.")));
+        assert!(lines.iter().any(|line| line.starts_with("25 is too low!")));
+        assert!(lines
+            .iter()
+            .any(|line| line.contains("Replayed as recorded: quit after 2 guesses")));
+    }
+
+    #[test]
+    fn replay_reports_where_it_diverged() {
+        let mut recording = recording(None);
+        if let Move::Guess { feedback, .. } = &mut recording.moves[2] {
+            feedback.direction = Direction::TooHigh;
+        }
+        let lines = replayed(&[recording]);
+
+        assert!(lines
+            .iter()
+            .any(|line| line.contains("diverged from the recording at guess 2")));
+    }
+}
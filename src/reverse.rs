@@ -0,0 +1,195 @@
+use std::io::{BufRead, Write};
+
+use crate::{
+    feedback::{Direction, Feedback},
+    game::GameError,
+    guesser::Guesser,
+    io::{write, WriteArgs},
+    menu::{menu, MenuError},
+};
+
+/// Play a round in reverse: the human thinks of a secret in [min, max] & the
+/// given Guesser guesses it, with the human answering each guess through a
+/// menu. Returns Ok with the number of guesses once the human says a guess is
+/// correct, or Err if the human quits, gives answers that contradict each
+/// other, or the streams fail.
+pub fn play_reverse(
+    min: usize,
+    max: usize,
+    guesser: &mut impl Guesser,
+    mut writer: impl Write,
+    mut reader: impl BufRead,
+) -> Result<usize, GameError> {
+    // the interval the secret must be in, given the human's answers so far
+    let (mut low, mut high) = (min, max);
+    let mut guesses = 0;
+
+    write(
+        &mut writer,
+        WriteArgs::Fmt(format_args!(
+            "Think of a number belonging to [{},{}] & I'll try to guess it.\n",
+            min, max
+        )),
+    )?;
+
+    loop {
+        if low > high {
+            return contradiction(&mut writer);
+        }
+
+        let guess = guesser.guess()?;
+        guesses += 1;
+
+        write(
+            &mut writer,
+            WriteArgs::Fmt(format_args!("\nIs it {}?", guess)),
+        )?;
+        let choices = ["higher", "lower", "correct", "quit"];
+        let answer = loop {
+            match menu(&choices, &mut writer, &mut reader) {
+                Ok(choice) => break choice,
+                Err(MenuError::Invalid(reason)) => {
+                    write(&mut writer, WriteArgs::Fmt(format_args!("{}\n", reason)))?
+                }
+                Err(MenuError::Io(err)) => return Err(GameError::Io(err)),
+            }
+        };
+
+        let direction = match answer {
+            // the secret is higher, so the guess was too low
+            1 => {
+                match guess.checked_add(1) {
+                    Some(above) => low = low.max(above),
+                    None => return contradiction(&mut writer),
+                }
+                Direction::TooLow
+            }
+            // the secret is lower, so the guess was too high
+            2 => {
+                match guess.checked_sub(1) {
+                    Some(below) => high = high.min(below),
+                    None => return contradiction(&mut writer),
+                }
+                Direction::TooHigh
+            }
+            3 => {
+                if guess < low || guess > high {
+                    write(
+                        &mut writer,
+                        WriteArgs::Fmt(format_args!(
+                            "{} can't be right, your answers already ruled it out!\n",
+                            guess
+                        )),
+                    )?;
+                    return Err(GameError::Contradiction);
+                }
+                guesser.feedback(Feedback::new(guess, Direction::Correct))?;
+                return Ok(guesses);
+            }
+            _ => {
+                write(&mut writer, WriteArgs::Str("Quitting...\n"))?;
+                return Err(GameError::Quit);
+            }
+        };
+
+        guesser.feedback(Feedback::new(guess, direction))?;
+    }
+}
+
+/// Tell the human their answers rule out every number.
+fn contradiction(writer: impl Write) -> Result<usize, GameError> {
+    write(
+        writer,
+        WriteArgs::Str("Your answers contradict each other, there's no number left it could be!\n"),
+    )?;
+    Err(GameError::Contradiction)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        io::test_utils::setup_io_with_many_inputs,
+        players::{BinaryPlayer, LinearPlayer},
+    };
+
+    use super::*;
+
+    #[test]
+    fn play_reverse_returns_number_of_guesses_when_human_says_correct() {
+        // binary search on [1, 3] guesses 2, then 3
+        let answers = ["1", "3"];
+        let (writer, reader) = setup_io_with_many_inputs(&answers);
+        let mut guesser = BinaryPlayer::new(1, 3);
+
+        assert_eq!(play_reverse(1, 3, &mut guesser, writer, reader).unwrap(), 2);
+    }
+
+    #[test]
+    fn play_reverse_calls_out_contradictory_answers() {
+        // binary search on [1, 3] guesses 2, then 3, then would need a
+        // number higher than 2 but lower than 3
+        let answers = ["1", "2"];
+        let (mut writer, reader) = setup_io_with_many_inputs(&answers);
+        let mut guesser = BinaryPlayer::new(1, 3);
+        let result = play_reverse(1, 3, &mut guesser, &mut writer, reader);
+
+        assert!(matches!(result, Err(GameError::Contradiction)));
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains("contradict")));
+    }
+
+    #[test]
+    fn play_reverse_calls_out_answers_past_the_ends_of_usize() {
+        // nothing's lower than 0 or higher than usize::MAX
+        let top = usize::MAX;
+        for (first_guess, answer) in [(0, "2"), (top, "1")] {
+            let (mut writer, reader) = setup_io_with_many_inputs(&[answer]);
+            let mut guesser = LinearPlayer::new(first_guess, top);
+            let result = play_reverse(0, top, &mut guesser, &mut writer, reader);
+
+            assert!(matches!(result, Err(GameError::Contradiction)));
+        }
+    }
+
+    #[test]
+    fn play_reverse_calls_out_correct_answer_that_was_ruled_out() {
+        // a guesser starting below the range guesses 0, which can't be correct
+        let answers = ["3"];
+        let (writer, reader) = setup_io_with_many_inputs(&answers);
+        let mut guesser = LinearPlayer::new(0, 3);
+        let result = play_reverse(1, 3, &mut guesser, writer, reader);
+
+        assert!(matches!(result, Err(GameError::Contradiction)));
+    }
+
+    #[test]
+    fn play_reverse_asks_again_after_invalid_answer() {
+        let answers = ["not a choice", "3"];
+        let (writer, reader) = setup_io_with_many_inputs(&answers);
+        let mut guesser = BinaryPlayer::new(1, 3);
+
+        assert_eq!(play_reverse(1, 3, &mut guesser, writer, reader).unwrap(), 1);
+    }
+
+    #[test]
+    fn play_reverse_returns_quit_if_human_quits() {
+        let answers = ["4"];
+        let (writer, reader) = setup_io_with_many_inputs(&answers);
+        let mut guesser = BinaryPlayer::new(1, 3);
+        let result = play_reverse(1, 3, &mut guesser, writer, reader);
+
+        assert!(matches!(result, Err(GameError::Quit)));
+    }
+
+    #[test]
+    fn play_reverse_returns_io_error_if_input_runs_out() {
+        let answers = ["1"];
+        let (writer, reader) = setup_io_with_many_inputs(&answers);
+        let mut guesser = BinaryPlayer::new(1, 3);
+        let result = play_reverse(1, 3, &mut guesser, writer, reader);
+
+        assert!(matches!(result, Err(GameError::Io(_))));
+    }
+}
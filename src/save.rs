@@ -0,0 +1,252 @@
+use std::{
+    fs,
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    difficulty::{Difficulty, Settings},
+    game::GameState,
+    hints::HintKind,
+};
+
+/// Name of the file a saved round is kept in, next to the scoreboard file.
+pub const SAVE_FILE: &str = "saved_game.tsv";
+
+/// A round left part way through, with everything needed to pick it up
+/// exactly where it stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedGame {
+    pub difficulty: Difficulty,
+    pub state: GameState,
+    /// Time into the round each guess was made, matching the state's history
+    pub times: Vec<Duration>,
+    /// Time spent playing the round so far
+    pub elapsed: Duration,
+}
+
+impl SavedGame {
+    /// Where the round saved alongside the given scoreboard file is kept.
+    pub fn path_for(scores: &Path) -> PathBuf {
+        scores.with_file_name(SAVE_FILE)
+    }
+
+    /// Load the round saved at the given path, or None if there isn't one.
+    /// Returns Err if the file can't be read or isn't a saved round.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Option<Self>> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents)
+                .map(Some)
+                .map_err(|reason| io::Error::new(ErrorKind::InvalidData, reason)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Save the round to the given path, replacing any round saved there.
+    /// Written to a temporary file first, like the scoreboard, so a crash
+    /// mid-write never leaves a half written save behind.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut tmp_path = path.to_path_buf().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(self.to_lines().as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    }
+
+    /// Delete the round saved at the given path, if there is one.
+    pub fn remove(path: impl AsRef<Path>) -> io::Result<()> {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Format the round as tab separated lines: the difficulty, range, guess
+    /// limit, secret & time spent, followed by every guess, with the time it
+    /// was made, & hint in the order they were made, so replaying them
+    /// rebuilds the same state.
+    fn to_lines(&self) -> String {
+        let settings = self.state.settings();
+        let limit = match settings.max_guesses {
+            Some(limit) => limit.to_string(),
+            None => String::from("-"),
+        };
+        let mut lines = vec![
+            format!("difficulty\t{}", self.difficulty.name()),
+            format!("range\t{}\t{}\t{}", settings.min, settings.max, limit),
+            format!("secret\t{}", self.state.secret()),
+            format!("elapsed\t{}", self.elapsed.as_millis()),
+        ];
+
+        let mut hints = self.state.hints().iter().peekable();
+        let history = self.state.history().iter().zip(&self.times);
+        for (i, (feedback, time)) in history.enumerate() {
+            while let Some(taken) = hints.next_if(|h| h.after == i) {
+                lines.push(format!("hint\t{}", taken.kind.name()));
+            }
+            lines.push(format!("guess\t{}\t{}", feedback.guess, time.as_millis()));
+        }
+        for taken in hints {
+            lines.push(format!("hint\t{}", taken.kind.name()));
+        }
+
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    /// Parse a saved round, replaying its guesses & hints.
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut lines = contents
+            .lines()
+            .map(|line| line.split('\t').collect::<Vec<_>>());
+        let mut field = |name: &str| match lines.next() {
+            Some(fields) if fields[0] == name => Ok(fields[1..].to_vec()),
+            _ => Err(format!("Saved game is missing its {}", name)),
+        };
+        let number = |value: &str| {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("'{}' isn't a number", value))
+        };
+
+        let name = field("difficulty")?.join("\t");
+        let settings = match field("range")?.as_slice() {
+            [min, max, "-"] => Settings {
+                min: number(min)?,
+                max: number(max)?,
+                max_guesses: None,
+            },
+            [min, max, limit] => Settings {
+                min: number(min)?,
+                max: number(max)?,
+                max_guesses: Some(number(limit)?),
+            },
+            _ => return Err(String::from("Saved game has a malformed range")),
+        };
+        let secret = match field("secret")?.as_slice() {
+            [secret] => number(secret)?,
+            _ => return Err(String::from("Saved game has a malformed secret")),
+        };
+        let elapsed = match field("elapsed")?.as_slice() {
+            [millis] => Duration::from_millis(number(millis)? as u64),
+            _ => return Err(String::from("Saved game has a malformed time")),
+        };
+        if secret < settings.min || secret > settings.max {
+            return Err(String::from("Saved game's secret is out of its range"));
+        }
+
+        let difficulty = Difficulty::from_parts(&name, settings);
+        let mut state = GameState::new(secret, settings);
+        let mut times = Vec::new();
+
+        for fields in lines {
+            match fields.as_slice() {
+                ["guess", guess, millis] => {
+                    state.guess(number(guess)?);
+                    times.push(Duration::from_millis(number(millis)? as u64));
+                }
+                ["hint", kind] => {
+                    let kind = HintKind::parse(kind)
+                        .ok_or_else(|| format!("There's no '{}' hint", kind))?;
+                    state.hint(kind);
+                }
+                _ => {
+                    return Err(format!(
+                        "Saved game has a malformed line '{}'",
+                        fields.join("\t")
+                    ))
+                }
+            }
+            if state.is_over() {
+                return Err(String::from("Saved game is already over"));
+            }
+        }
+
+        Ok(SavedGame {
+            difficulty,
+            state,
+            times,
+            elapsed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir()
+            .join(format!("guessing_game_save_test_{}", std::process::id()))
+            .join(name)
+    }
+
+    fn saved_game() -> SavedGame {
+        let mut state = GameState::new(37, Difficulty::Normal.settings());
+        state.hint(HintKind::Near);
+        state.guess(50);
+        state.guess(25);
+        state.hint(HintKind::Divisor);
+        state.hint(HintKind::Divisor);
+        state.guess(40);
+        state.hint(HintKind::NextGuess);
+
+        SavedGame {
+            difficulty: Difficulty::Normal,
+            state,
+            times: [2_000, 6_500, 11_000].map(Duration::from_millis).to_vec(),
+            elapsed: Duration::from_millis(12_345),
+        }
+    }
+
+    #[test]
+    fn saved_game_loads_exactly_as_it_was() {
+        let path = temp_path("round_trip.tsv");
+        let saved = saved_game();
+        saved.save(&path).unwrap();
+
+        assert_eq!(SavedGame::load(&path).unwrap(), Some(saved));
+        SavedGame::remove(&path).unwrap();
+        assert_eq!(SavedGame::load(&path).unwrap(), None);
+    }
+
+    #[test]
+    fn custom_settings_load_as_custom_difficulty() {
+        let settings = Settings {
+            min: 5,
+            max: 500,
+            max_guesses: None,
+        };
+        let saved = SavedGame {
+            difficulty: Difficulty::Custom(settings),
+            state: GameState::new(42, settings),
+            times: Vec::new(),
+            elapsed: Duration::ZERO,
+        };
+
+        assert_eq!(SavedGame::parse(&saved.to_lines()), Ok(saved));
+    }
+
+    #[test]
+    fn malformed_saves_are_rejected() {
+        let contents = saved_game().to_lines();
+
+        assert!(SavedGame::parse("").is_err());
+        assert!(SavedGame::parse(&contents.replace("hint\tnear", "hint\teverything")).is_err());
+        // the round would already have been won
+        assert!(SavedGame::parse(&format!("{}guess\t37\t12000\n", contents)).is_err());
+        // every guess has the time it was made
+        assert!(SavedGame::parse(&contents.replace("guess\t50\t2000", "guess\t50")).is_err());
+    }
+}
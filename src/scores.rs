@@ -0,0 +1,540 @@
+use std::{
+    cmp::Reverse,
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::io::{write, WriteArgs};
+
+/// Environment variable that overrides where the scoreboard file is kept.
+pub const SCORES_ENV: &str = "GUESSING_GAME_SCORES";
+/// Number of entries shown in each high-score table.
+pub const HIGH_SCORES_SHOWN: usize = 10;
+
+/// How a recorded game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Won,
+    Lost,
+    Quit,
+}
+
+impl Outcome {
+    /// Short lowercase name used in the scoreboard file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Outcome::Won => "won",
+            Outcome::Lost => "lost",
+            Outcome::Quit => "quit",
+        }
+    }
+
+    /// The Outcome with the given name, see `name`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "won" => Some(Outcome::Won),
+            "lost" => Some(Outcome::Lost),
+            "quit" => Some(Outcome::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// One finished game, as stored on the scoreboard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreRecord {
+    pub player: String,
+    pub difficulty: String,
+    pub min: usize,
+    pub max: usize,
+    pub guesses: usize,
+    pub duration: Duration,
+    pub outcome: Outcome,
+    /// Points scored, see crate::scoring
+    pub score: u32,
+    /// Number of hints taken
+    pub hints: usize,
+}
+
+impl ScoreRecord {
+    /// Format the record as one tab separated line of the scoreboard file.
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            clean(&self.player),
+            clean(&self.difficulty),
+            self.min,
+            self.max,
+            self.guesses,
+            self.duration.as_millis(),
+            self.outcome.name(),
+            self.score,
+            self.hints,
+        )
+    }
+
+    /// Parse one line of the scoreboard file, returning None if malformed.
+    /// Lines written before scores were kept have no score or hints, so
+    /// score nothing & took no hints.
+    fn from_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let (score, hints) = match fields.len() {
+            7 => (0, 0),
+            9 => (fields[7].parse().ok()?, fields[8].parse().ok()?),
+            _ => return None,
+        };
+
+        Some(ScoreRecord {
+            player: fields[0].to_string(),
+            difficulty: fields[1].to_string(),
+            min: fields[2].parse().ok()?,
+            max: fields[3].parse().ok()?,
+            guesses: fields[4].parse().ok()?,
+            duration: Duration::from_millis(fields[5].parse().ok()?),
+            outcome: Outcome::parse(fields[6])?,
+            score,
+            hints,
+        })
+    }
+}
+
+/// Replace characters that would break the scoreboard's line format.
+fn clean(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Totals for every game a single player has recorded.
+#[derive(Debug, PartialEq)]
+pub struct PlayerStats {
+    pub player: String,
+    pub games: usize,
+    pub wins: usize,
+    /// Average guesses over games won, if any were won.
+    pub average_guesses: Option<f64>,
+    pub best_streak: usize,
+    /// Highest score over every game, comparable across ranges.
+    pub best_score: u32,
+}
+
+impl PlayerStats {
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games as f64
+    }
+}
+
+/// Every game recorded so far, backed by a file that's rewritten atomically
+/// each time a game is added. The file is read again just before each
+/// rewrite, so sessions sharing it keep each other's games.
+pub struct Scoreboard {
+    path: PathBuf,
+    records: Vec<ScoreRecord>,
+}
+
+impl Scoreboard {
+    /// Load the scoreboard kept at the given path, starting empty if the
+    /// file doesn't exist yet. A malformed line is an InvalidData error
+    /// rather than being skipped, as the next save would drop it for good.
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        let records = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(i, line)| {
+                ScoreRecord::from_line(line).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Line {} of the scoreboard is malformed", i + 1),
+                    )
+                })
+            })
+            .collect::<io::Result<_>>()?;
+
+        Ok(Scoreboard { path, records })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn records(&self) -> &[ScoreRecord] {
+        &self.records
+    }
+
+    /// Record a finished game & save the scoreboard, along with any games
+    /// other sessions sharing the file have added since it was loaded.
+    pub fn add(&mut self, record: ScoreRecord) -> io::Result<()> {
+        match Self::load(&self.path) {
+            Ok(latest) => self.records = latest.records,
+            // keep the game for this session at least, without touching a
+            // file that couldn't be read
+            Err(err) => {
+                self.records.push(record);
+                return Err(err);
+            }
+        }
+        self.records.push(record);
+        self.save()
+    }
+
+    /// Write every record to a temporary file next to the scoreboard file,
+    /// then rename it over the old one so a crash mid-write never leaves a
+    /// half written scoreboard behind.
+    fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let mut file = fs::File::create(&tmp_path)?;
+        for record in &self.records {
+            writeln!(file, "{}", record.to_line())?;
+        }
+        file.sync_all()?;
+
+        fs::rename(&tmp_path, &self.path)
+    }
+
+    /// The best won games for each difficulty & range played, highest score
+    /// first with ties going to the fewest guesses, then the fastest game.
+    pub fn high_scores(&self) -> Vec<(String, Vec<&ScoreRecord>)> {
+        let mut tables: Vec<(String, Vec<&ScoreRecord>)> = Vec::new();
+
+        for record in self.records.iter().filter(|r| r.outcome == Outcome::Won) {
+            let title = format!("{} [{},{}]", record.difficulty, record.min, record.max);
+            match tables.iter_mut().find(|(t, _)| *t == title) {
+                Some((_, table)) => table.push(record),
+                None => tables.push((title, vec![record])),
+            }
+        }
+
+        for (_, table) in tables.iter_mut() {
+            table.sort_by_key(|r| (Reverse(r.score), r.guesses, r.duration));
+            table.truncate(HIGH_SCORES_SHOWN);
+        }
+        tables.sort_by(|a, b| a.0.cmp(&b.0));
+
+        tables
+    }
+
+    /// Stats for every player with a recorded game, in the order they
+    /// first played.
+    pub fn player_stats(&self) -> Vec<PlayerStats> {
+        let mut players: Vec<&str> = Vec::new();
+        for record in &self.records {
+            if !players.contains(&record.player.as_str()) {
+                players.push(&record.player);
+            }
+        }
+
+        players
+            .into_iter()
+            .map(|player| {
+                let games: Vec<&ScoreRecord> =
+                    self.records.iter().filter(|r| r.player == player).collect();
+                let won: Vec<&&ScoreRecord> =
+                    games.iter().filter(|r| r.outcome == Outcome::Won).collect();
+
+                let mut best_streak = 0;
+                let mut streak = 0;
+                for record in &games {
+                    if record.outcome == Outcome::Won {
+                        streak += 1;
+                        best_streak = best_streak.max(streak);
+                    } else {
+                        streak = 0;
+                    }
+                }
+
+                PlayerStats {
+                    player: player.to_string(),
+                    games: games.len(),
+                    wins: won.len(),
+                    average_guesses: match won.len() {
+                        0 => None,
+                        wins => {
+                            Some(won.iter().map(|r| r.guesses).sum::<usize>() as f64 / wins as f64)
+                        }
+                    },
+                    best_streak,
+                    best_score: games.iter().map(|r| r.score).max().unwrap_or(0),
+                }
+            })
+            .collect()
+    }
+
+    /// Write the high-score tables & per-player stats to the write stream.
+    pub fn render(&self, mut writer: impl Write) -> io::Result<()> {
+        let tables = self.high_scores();
+        if tables.is_empty() {
+            write(&mut writer, WriteArgs::Str("\nNo games won yet!\n"))?;
+        }
+
+        for (title, table) in tables {
+            write(
+                &mut writer,
+                WriteArgs::Fmt(format_args!("\nHigh scores: {}\n", title)),
+            )?;
+            for (rank, record) in table.iter().enumerate() {
+                write(
+                    &mut writer,
+                    WriteArgs::Fmt(format_args!(
+                        "{:>2}. {:<16} {:>4} guesses {:>8.1}s {:>6} points\n",
+                        rank + 1,
+                        record.player,
+                        record.guesses,
+                        record.duration.as_secs_f64(),
+                        record.score,
+                    )),
+                )?;
+            }
+        }
+
+        let stats = self.player_stats();
+        if stats.is_empty() {
+            return Ok(());
+        }
+
+        write(
+            &mut writer,
+            WriteArgs::Fmt(format_args!(
+                "\n{:<16} {:>5} {:>8} {:>12} {:>11} {:>10}\n",
+                "player", "games", "win rate", "avg guesses", "best streak", "best score"
+            )),
+        )?;
+        for s in stats {
+            let average = match s.average_guesses {
+                Some(average) => format!("{:.1}", average),
+                None => String::from("-"),
+            };
+            write(
+                &mut writer,
+                WriteArgs::Fmt(format_args!(
+                    "{:<16} {:>5} {:>7.0}% {:>12} {:>11} {:>10}\n",
+                    s.player,
+                    s.games,
+                    s.win_rate() * 100.0,
+                    average,
+                    s.best_streak,
+                    s.best_score,
+                )),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Where the scoreboard file is kept unless a path is given on the command
+/// line: $GUESSING_GAME_SCORES if set, otherwise scores.tsv in the XDG data
+/// directory.
+pub fn default_path() -> PathBuf {
+    if let Some(path) = env::var_os(SCORES_ENV) {
+        return PathBuf::from(path);
+    }
+
+    let data_dir = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => match env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(".local").join("share"),
+            None => PathBuf::from("."),
+        },
+    };
+
+    data_dir.join("guessing_game").join("scores.tsv")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::test_utils::setup_io;
+
+    use super::*;
+
+    fn record(player: &str, guesses: usize, outcome: Outcome) -> ScoreRecord {
+        ScoreRecord {
+            player: String::from(player),
+            difficulty: String::from("normal"),
+            min: 0,
+            max: 100,
+            guesses,
+            duration: Duration::from_millis(1500),
+            outcome,
+            score: 0,
+            hints: 0,
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("guessing_game_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn record_round_trips_through_line_format() {
+        let original = record("ada", 7, Outcome::Won);
+
+        assert_eq!(ScoreRecord::from_line(&original.to_line()), Some(original));
+    }
+
+    #[test]
+    fn record_reads_lines_written_before_scores_were_kept() {
+        let record = ScoreRecord::from_line("ada\tnormal\t0\t100\t7\t1500\twon").unwrap();
+
+        assert_eq!((record.guesses, record.score, record.hints), (7, 0, 0));
+    }
+
+    #[test]
+    fn record_line_replaces_tabs_in_player_name() {
+        let line = record("a\tb", 7, Outcome::Won).to_line();
+
+        assert_eq!(ScoreRecord::from_line(&line).unwrap().player, "a b");
+    }
+
+    #[test]
+    fn load_starts_empty_when_file_is_missing() {
+        let scoreboard = Scoreboard::load(temp_path("missing.tsv")).unwrap();
+
+        assert!(scoreboard.records().is_empty());
+    }
+
+    #[test]
+    fn load_rejects_malformed_lines_instead_of_dropping_them() {
+        let path = temp_path("malformed.tsv");
+        let line = record("ada", 7, Outcome::Won).to_line();
+        fs::write(&path, format!("{}\nnot a record\n", line)).unwrap();
+
+        let err = Scoreboard::load(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("Line 2"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn sessions_sharing_a_file_keep_each_others_games() {
+        let path = temp_path("shared.tsv");
+        let _ = fs::remove_file(&path);
+        let mut first = Scoreboard::load(&path).unwrap();
+        let mut second = Scoreboard::load(&path).unwrap();
+        first.add(record("ada", 7, Outcome::Won)).unwrap();
+        second.add(record("bob", 3, Outcome::Lost)).unwrap();
+
+        let players: Vec<String> = Scoreboard::load(&path)
+            .unwrap()
+            .records()
+            .iter()
+            .map(|r| r.player.clone())
+            .collect();
+        assert_eq!(players, ["ada", "bob"]);
+        assert_eq!(second.records().len(), 2);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn add_saves_records_that_load_again() {
+        let path = temp_path("saved.tsv");
+        let _ = fs::remove_file(&path);
+        let mut scoreboard = Scoreboard::load(&path).unwrap();
+        scoreboard.add(record("ada", 7, Outcome::Won)).unwrap();
+        scoreboard.add(record("bob", 3, Outcome::Quit)).unwrap();
+
+        let loaded = Scoreboard::load(&path).unwrap();
+        assert_eq!(loaded.records(), scoreboard.records());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn high_scores_only_include_wins_ordered_by_guesses() {
+        let scoreboard = Scoreboard {
+            path: temp_path("unused.tsv"),
+            records: vec![
+                record("ada", 7, Outcome::Won),
+                record("bob", 2, Outcome::Lost),
+                record("cat", 4, Outcome::Won),
+            ],
+        };
+        let tables = scoreboard.high_scores();
+
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].0, "normal [0,100]");
+        let players: Vec<&str> = tables[0].1.iter().map(|r| r.player.as_str()).collect();
+        assert_eq!(players, ["cat", "ada"]);
+    }
+
+    #[test]
+    fn high_scores_rank_by_score_before_guesses() {
+        let scoreboard = Scoreboard {
+            path: temp_path("unused.tsv"),
+            records: vec![
+                record("ada", 4, Outcome::Won),
+                ScoreRecord {
+                    score: 900,
+                    ..record("bob", 7, Outcome::Won)
+                },
+            ],
+        };
+        let tables = scoreboard.high_scores();
+        let players: Vec<&str> = tables[0].1.iter().map(|r| r.player.as_str()).collect();
+
+        assert_eq!(players, ["bob", "ada"]);
+        assert_eq!(scoreboard.player_stats()[1].best_score, 900);
+    }
+
+    #[test]
+    fn player_stats_count_games_wins_and_streaks() {
+        let scoreboard = Scoreboard {
+            path: temp_path("unused.tsv"),
+            records: vec![
+                record("ada", 4, Outcome::Won),
+                record("ada", 6, Outcome::Won),
+                record("ada", 10, Outcome::Lost),
+                record("ada", 8, Outcome::Won),
+                record("bob", 1, Outcome::Quit),
+            ],
+        };
+        let stats = scoreboard.player_stats();
+
+        assert_eq!(
+            stats[0],
+            PlayerStats {
+                player: String::from("ada"),
+                games: 4,
+                wins: 3,
+                average_guesses: Some(6.0),
+                best_streak: 2,
+                best_score: 0,
+            }
+        );
+        assert_eq!(stats[1].average_guesses, None);
+        assert_eq!(stats[1].win_rate(), 0.0);
+    }
+
+    #[test]
+    fn render_shows_tables_and_stats() {
+        let (mut writer, _) = setup_io();
+        let scoreboard = Scoreboard {
+            path: temp_path("unused.tsv"),
+            records: vec![record("ada", 4, Outcome::Won)],
+        };
+        scoreboard.render(&mut writer).unwrap();
+
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains("High scores: normal [0,100]")));
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.starts_with("ada") && line.contains("100%")));
+    }
+}
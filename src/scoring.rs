@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use crate::game::GameState;
+
+/// Points for winning a round in the optimal number of guesses, before time,
+/// hints & difficulty are taken into account.
+pub const BASE_SCORE: f64 = 1000.0;
+/// Time allowed per optimal guess before the time factor starts to drop.
+pub const PAR_PER_GUESS: Duration = Duration::from_secs(5);
+/// Lowest the time factor goes, however long a round takes.
+pub const MIN_TIME_FACTOR: f64 = 0.5;
+
+/// How a round's score was worked out. Guesses are measured against the
+/// optimal ceil(log2 n) for the round's range, so rounds played on
+/// different ranges can be compared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    pub guesses: usize,
+    pub optimal: usize,
+    /// BASE_SCORE scaled by how close the guesses were to optimal
+    pub guess_points: f64,
+    /// From 1 for a round played within par time down to MIN_TIME_FACTOR
+    pub time_factor: f64,
+    pub hint_penalty: u32,
+    /// From the round's Settings, see Settings::multiplier
+    pub multiplier: f64,
+    pub total: u32,
+}
+
+impl Score {
+    /// Score a round that took `elapsed` to play. Rounds that weren't won
+    /// score nothing.
+    pub fn new(state: &GameState, elapsed: Duration) -> Self {
+        let settings = state.settings();
+        let guesses = state.guesses();
+        let optimal = settings.optimal_guesses();
+
+        let guess_points = match guesses {
+            0 => 0.0,
+            _ => BASE_SCORE * (optimal as f64 / guesses as f64).min(1.0),
+        };
+        let par = PAR_PER_GUESS.as_secs_f64() * optimal as f64;
+        let time_factor = match elapsed.as_secs_f64() {
+            secs if secs <= par => 1.0,
+            secs => MIN_TIME_FACTOR + (1.0 - MIN_TIME_FACTOR) * par / secs,
+        };
+        let hint_penalty = state.hint_penalty();
+        let multiplier = settings.multiplier();
+
+        let total = match state.is_won() {
+            true => ((guess_points * time_factor - hint_penalty as f64).max(0.0) * multiplier)
+                .round() as u32,
+            false => 0,
+        };
+
+        Score {
+            guesses,
+            optimal,
+            guess_points,
+            time_factor,
+            hint_penalty,
+            multiplier,
+            total,
+        }
+    }
+
+    /// Explain how the score was worked out, e.g.
+    /// `"1190 points: 700 for 10 guesses (7 is optimal) x 1.00 for time - 0 for hints, x 1.7 for difficulty"`.
+    pub fn describe(&self) -> String {
+        format!(
+            "{} points: {:.0} for {} guesses ({} is optimal) x {:.2} for time - {} for hints, x {:.1} for difficulty",
+            self.total,
+            self.guess_points,
+            self.guesses,
+            self.optimal,
+            self.time_factor,
+            self.hint_penalty,
+            self.multiplier,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        difficulty::{Difficulty, Settings},
+        hints::HintKind,
+    };
+
+    use super::*;
+
+    /// Play a round of the given Settings guessing up from its min, taking
+    /// the given hints before the winning guess.
+    fn play(settings: Settings, secret: usize, hints: &[HintKind]) -> GameState {
+        let mut state = GameState::new(secret, settings);
+        for guess in settings.min..secret {
+            state.guess(guess);
+        }
+        for hint in hints {
+            state.hint(*hint);
+        }
+        state.guess(secret);
+        state
+    }
+
+    #[test]
+    fn unfinished_rounds_score_nothing() {
+        let mut state = GameState::new(40, Settings::default());
+        state.guess(50);
+
+        assert_eq!(Score::new(&state, Duration::ZERO).total, 0);
+    }
+
+    #[test]
+    fn optimal_round_scores_base_times_multiplier() {
+        let settings = Difficulty::Normal.settings();
+        let state = play(settings, settings.optimal_guesses() - 1, &[]);
+
+        assert_eq!(Score::new(&state, Duration::ZERO).total, 1700);
+    }
+
+    #[test]
+    fn extra_guesses_time_and_hints_cost_points() {
+        let settings = Settings::default();
+        let fast = Score::new(&play(settings, 9, &[]), Duration::ZERO);
+        let slow = Score::new(&play(settings, 9, &[]), Duration::from_secs(600));
+        let hinted = Score::new(&play(settings, 9, &[HintKind::Parity]), Duration::ZERO);
+        let longer = Score::new(&play(settings, 19, &[]), Duration::ZERO);
+
+        assert_eq!(fast.total, 700);
+        assert!(slow.total < fast.total && slow.total >= 350);
+        assert_eq!(hinted.total, 700 - HintKind::Parity.cost());
+        assert!(longer.total < fast.total);
+    }
+
+    #[test]
+    fn rounds_on_different_ranges_are_comparable() {
+        let small = Settings {
+            min: 0,
+            max: 15,
+            max_guesses: None,
+        };
+        let big = Settings {
+            min: 0,
+            max: 1023,
+            max_guesses: None,
+        };
+        // both found in twice the optimal number of guesses
+        let small = Score::new(&play(small, 7, &[]), Duration::ZERO);
+        let big = Score::new(&play(big, 19, &[]), Duration::ZERO);
+
+        assert_eq!(small.total, big.total);
+    }
+}
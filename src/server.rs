@@ -0,0 +1,581 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, BufRead, BufReader, Read},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Sender},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    difficulty::Settings,
+    feedback::render,
+    game::{GameState, Rejection},
+    io::{write, WriteArgs},
+    random::{NumberGenerator, SecretSource},
+};
+
+/// Port the server listens on when `--port` isn't given.
+pub const DEFAULT_PORT: u16 = 7878;
+/// Longest line a player may send, in bytes. Anyone sending a longer one is
+/// disconnected rather than buffered without end.
+pub const MAX_LINE: usize = 1024;
+/// Time a write to a player may take before they're disconnected, as every
+/// write is made on the game thread & a stalled player would hold up
+/// everyone else.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Something that happened on one of the connections, passed to the thread
+/// running the game. Connections are numbered in the order they were made.
+enum Event {
+    Joined(usize, TcpStream),
+    Line(usize, String),
+    /// Sent a line longer than MAX_LINE
+    Overlong(usize),
+    Left(usize),
+}
+
+/// A server several players connect to over TCP, e.g. with `nc` or
+/// `telnet`, to race each other to the same secrets. Players wait in a
+/// lobby until one of them starts a round, then everyone guesses at once;
+/// each sees the feedback on their own guesses & how many guesses everyone
+/// else has made, & the first to find the secret wins the round.
+pub struct Server {
+    listener: TcpListener,
+    settings: Settings,
+    rnd: NumberGenerator,
+}
+
+impl Server {
+    /// Listen on the given address, playing every round with the given
+    /// Settings & secrets from the given generator.
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        settings: Settings,
+        rnd: NumberGenerator,
+    ) -> io::Result<Self> {
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+            settings,
+            rnd,
+        })
+    }
+
+    /// The address the server is listening on, e.g. to find the port
+    /// picked when bound to port 0.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept players & run the game until the process ends. Connections
+    /// are read on threads of their own, while every rule is applied on
+    /// this one, in the order things happened.
+    pub fn run(self) -> io::Result<()> {
+        let (sender, events) = mpsc::channel();
+        let listener = self.listener;
+        thread::spawn(move || accept(listener, sender));
+
+        let mut lobby = Lobby {
+            settings: self.settings,
+            rnd: self.rnd,
+            players: BTreeMap::new(),
+            round: 0,
+            playing: false,
+        };
+        for event in events {
+            lobby.handle(event);
+        }
+
+        Ok(())
+    }
+}
+
+/// Accept connections, reading each one's lines on a thread of its own.
+fn accept(listener: TcpListener, events: Sender<Event>) {
+    for (id, stream) in listener.incoming().enumerate() {
+        // a connection that failed before it was accepted has no one to
+        // tell
+        let Ok(stream) = stream else { continue };
+        let Ok(reader) = stream.try_clone() else {
+            continue;
+        };
+        if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+            continue;
+        }
+        if events.send(Event::Joined(id, stream)).is_err() {
+            return;
+        }
+
+        let events = events.clone();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            while let Some(event) = read_line(id, &mut reader) {
+                let overlong = matches!(event, Event::Overlong(_));
+                if events.send(event).is_err() || overlong {
+                    break;
+                }
+            }
+            let _ = events.send(Event::Left(id));
+        });
+    }
+}
+
+/// Read the next line from a connection, as an Event::Line, or
+/// Event::Overlong if it's longer than MAX_LINE. Returns None once the
+/// connection is closed or sends something that isn't UTF-8.
+fn read_line(id: usize, reader: &mut impl BufRead) -> Option<Event> {
+    let mut line = Vec::new();
+    // one byte more than allowed, to tell a line that fits from one that
+    // doesn't
+    let limit = MAX_LINE as u64 + 1;
+    match reader.take(limit).read_until(b'\n', &mut line) {
+        Ok(0) | Err(_) => return None,
+        Ok(_) => (),
+    }
+    if line.len() > MAX_LINE && !line.ends_with(b"\n") {
+        return Some(Event::Overlong(id));
+    }
+
+    String::from_utf8(line)
+        .ok()
+        .map(|line| Event::Line(id, line))
+}
+
+/// Someone connected to the server.
+struct Player {
+    /// None until they've said who they are
+    name: Option<String>,
+    stream: TcpStream,
+    wins: usize,
+    /// Their guesses in the current round, if they're playing it
+    round: Option<GameState>,
+}
+
+/// Everyone connected to the server & the round they're playing, if any.
+struct Lobby {
+    settings: Settings,
+    rnd: NumberGenerator,
+    players: BTreeMap<usize, Player>,
+    /// Number of rounds started so far
+    round: usize,
+    playing: bool,
+}
+
+impl Lobby {
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Joined(id, stream) => {
+                let player = Player {
+                    name: None,
+                    stream,
+                    wins: 0,
+                    round: None,
+                };
+                self.players.insert(id, player);
+                self.send(id, "Welcome to the guessing game server!");
+                self.send(id, "What's your name?");
+            }
+            Event::Line(id, line) => self.line(id, line.trim()),
+            Event::Overlong(id) => {
+                self.send(
+                    id,
+                    &format!("Lines can't be longer than {} bytes, bye!", MAX_LINE),
+                );
+                if let Some(player) = self.players.get(&id) {
+                    let _ = player.stream.shutdown(Shutdown::Both);
+                }
+            }
+            Event::Left(id) => self.left(id),
+        }
+    }
+
+    fn line(&mut self, id: usize, line: &str) {
+        let Some(player) = self.players.get(&id) else {
+            return;
+        };
+        if player.name.is_none() {
+            return self.name(id, line);
+        }
+
+        match line {
+            "" => (),
+            "start" => self.start(id),
+            "scores" => {
+                let scoreboard = self.scoreboard();
+                self.send(id, &scoreboard);
+            }
+            "help" => self.send(id, HELP),
+            // close the connection, & the player leaves once its reader
+            // sees it's closed
+            "quit" => {
+                self.send(id, "Bye!");
+                let _ = self.players[&id].stream.shutdown(Shutdown::Both);
+            }
+            guess => match guess.parse() {
+                Ok(guess) => self.guess(id, guess),
+                Err(_) => self.send(
+                    id,
+                    &format!(
+                        "Please guess a number in [{}, {}], or type 'help' to see the commands.",
+                        self.settings.min, self.settings.max
+                    ),
+                ),
+            },
+        }
+    }
+
+    /// Name a player who just joined, unless the name's taken.
+    fn name(&mut self, id: usize, name: &str) {
+        let name = match name {
+            "" => format!("player{}", id + 1),
+            name => name.to_string(),
+        };
+        if self
+            .named()
+            .any(|(_, player)| player.name.as_ref() == Some(&name))
+        {
+            return self.send(id, "That name's taken, pick another.");
+        }
+
+        self.players.get_mut(&id).unwrap().name = Some(name.clone());
+        self.send(id, &format!("Hi {}! {}", name, HELP));
+        if self.playing {
+            self.send(id, "A round is under way, you'll join the next one.");
+        }
+        self.broadcast(&format!("{} joined.", name), Some(id));
+    }
+
+    fn left(&mut self, id: usize) {
+        let Some(player) = self.players.remove(&id) else {
+            return;
+        };
+        if let Some(name) = player.name {
+            self.broadcast(&format!("{} left.", name), None);
+            self.end_if_everyone_is_out();
+        }
+    }
+
+    /// Start a round for everyone who's named, with a fresh secret.
+    fn start(&mut self, id: usize) {
+        if self.playing {
+            return self.send(id, "A round is already under way.");
+        }
+
+        let settings = self.settings;
+        let secret = self.rnd.gen_secret_in(settings.min, settings.max);
+        for player in self.players.values_mut() {
+            if player.name.is_some() {
+                player.round = Some(GameState::new(secret, settings));
+            }
+        }
+        self.round += 1;
+        self.playing = true;
+
+        let limit = match settings.max_guesses {
+            Some(limit) => format!("{} guesses each", limit),
+            None => String::from("unlimited guesses"),
+        };
+        self.broadcast(
+            &format!(
+                "Round {} has started! Guess a number in [{}, {}], {}. First to find it wins.",
+                self.round, settings.min, settings.max, limit
+            ),
+            None,
+        );
+    }
+
+    fn guess(&mut self, id: usize, guess: usize) {
+        if !self.playing {
+            return self.send(id, "No round is under way, type 'start' to begin one.");
+        }
+        let (min, max) = (self.settings.min, self.settings.max);
+        let player = self.players.get_mut(&id).unwrap();
+        let name = player.name.clone().unwrap_or_default();
+        let state = match player.round.as_mut() {
+            Some(state) if !state.is_over() => state,
+            Some(_) => return self.send(id, "You're out of guesses, wait for the next round."),
+            None => return self.send(id, "You'll join the next round."),
+        };
+        match state.check(guess) {
+            Ok(()) => (),
+            Err(Rejection::OutOfRange) => {
+                return self.send(id, &format!("Guesses must be in [{}, {}].", min, max))
+            }
+            Err(Rejection::Repeated) => {
+                return self.send(id, &format!("You already guessed {}.", guess))
+            }
+        }
+
+        let feedback = state.guess(guess);
+        let guesses = state.guesses();
+        let out = state.is_over() && !feedback.is_correct();
+        let plural = if guesses == 1 { "" } else { "es" };
+
+        if feedback.is_correct() {
+            self.players.get_mut(&id).unwrap().wins += 1;
+            self.broadcast(
+                &format!(
+                    "{} found the secret {} in {} guess{} & wins round {}!",
+                    name, guess, guesses, plural, self.round
+                ),
+                None,
+            );
+            return self.end_round();
+        }
+
+        self.send(id, &render(&feedback));
+        if out {
+            self.send(id, "Out of guesses! You're out of this round.");
+            self.broadcast(&format!("{} is out of guesses.", name), Some(id));
+        }
+        // keep everyone up to date with how the round's going
+        let scoreboard = self.scoreboard();
+        self.broadcast(&scoreboard, None);
+        if out {
+            self.end_if_everyone_is_out();
+        }
+    }
+
+    /// End the round if no one playing it has guesses left.
+    fn end_if_everyone_is_out(&mut self) {
+        let secret = self
+            .players
+            .values()
+            .find_map(|player| player.round.as_ref())
+            .map(GameState::secret);
+        let everyone_out = self
+            .players
+            .values()
+            .filter_map(|player| player.round.as_ref())
+            .all(GameState::is_over);
+
+        if self.playing && everyone_out {
+            let message = match secret {
+                Some(secret) => format!("Nobody found the secret, it was {}.", secret),
+                None => String::from("Everyone left, so the round is over."),
+            };
+            self.broadcast(&message, None);
+            self.end_round();
+        }
+    }
+
+    fn end_round(&mut self) {
+        self.playing = false;
+        let scoreboard = self.scoreboard();
+        self.broadcast(&scoreboard, None);
+        for player in self.players.values_mut() {
+            player.round = None;
+        }
+        self.broadcast("Type 'start' to play another round.", None);
+    }
+
+    /// Every named player's wins, most first, & their guesses in the
+    /// current round.
+    fn scoreboard(&self) -> String {
+        let mut players: Vec<&Player> = self.named().map(|(_, player)| player).collect();
+        players.sort_by_key(|player| (std::cmp::Reverse(player.wins), player.name.clone()));
+
+        let mut lines = vec![String::from("Scoreboard:")];
+        for player in players {
+            let wins = if player.wins == 1 { "win" } else { "wins" };
+            let mut line = format!(
+                "  {}: {} {}",
+                player.name.as_deref().unwrap_or_default(),
+                player.wins,
+                wins
+            );
+            if let (true, Some(state)) = (self.playing, &player.round) {
+                line.push_str(&format!(", {} guesses this round", state.guesses()));
+                if state.is_over() {
+                    line.push_str(", out of guesses");
+                }
+            }
+            lines.push(line);
+        }
+
+        lines.join("\r\n")
+    }
+
+    fn named(&self) -> impl Iterator<Item = (&usize, &Player)> {
+        self.players
+            .iter()
+            .filter(|(_, player)| player.name.is_some())
+    }
+
+    /// Send a line to one player. A player who can't be written to is
+    /// disconnected, & leaves once their reader notices.
+    fn send(&self, id: usize, text: &str) {
+        if let Some(player) = self.players.get(&id) {
+            // \r\n so telnet starts each line at the left margin
+            let sent = write(&player.stream, WriteArgs::Fmt(format_args!("{}\r\n", text)));
+            if sent.is_err() {
+                let _ = player.stream.shutdown(Shutdown::Both);
+            }
+        }
+    }
+
+    /// Send a line to every named player, except the given one if any.
+    fn broadcast(&self, text: &str, except: Option<usize>) {
+        let ids: Vec<usize> = self.named().map(|(id, _)| *id).collect();
+        for id in ids.into_iter().filter(|id| Some(*id) != except) {
+            self.send(id, text);
+        }
+    }
+}
+
+const HELP: &str = "Type 'start' to begin a round, a number to guess, 'scores' to see the scoreboard or 'quit' to leave.";
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, time::Duration};
+
+    use crate::constants::{MAX_SECRET, MIN_SECRET};
+
+    use super::*;
+
+    /// A player connected over loopback.
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Client {
+        /// Connect & join with the given name.
+        fn join(addr: SocketAddr, name: &str) -> Self {
+            let writer = TcpStream::connect(addr).unwrap();
+            // fail rather than hang if an expected line never comes
+            writer
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let mut client = Client {
+                reader: BufReader::new(writer.try_clone().unwrap()),
+                writer,
+            };
+
+            client.expect("What's your name?");
+            client.send(name);
+            client.expect(&format!("Hi {}!", name));
+            client
+        }
+
+        fn send(&mut self, line: &str) {
+            write!(self.writer, "{}\r\n", line).unwrap();
+        }
+
+        /// Read lines until one contains the given text, & return it.
+        fn expect(&mut self, text: &str) -> String {
+            loop {
+                let mut line = String::new();
+                assert_ne!(self.reader.read_line(&mut line).unwrap(), 0);
+                if line.contains(text) {
+                    return line.trim_end().to_string();
+                }
+            }
+        }
+    }
+
+    /// Start a server playing with the given Settings on a free port, &
+    /// return its address & the secret its first round will have.
+    fn serve(settings: Settings) -> (SocketAddr, usize) {
+        let rnd = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, 9);
+        let secret = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, 9)
+            .gen_secret_in(settings.min, settings.max);
+        let server = Server::bind("127.0.0.1:0", settings, rnd).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+
+        (addr, secret)
+    }
+
+    fn wrong_guess(secret: usize) -> usize {
+        if secret == 0 {
+            1
+        } else {
+            secret - 1
+        }
+    }
+
+    #[test]
+    fn first_correct_guess_wins_the_round_for_everyone() {
+        let (addr, secret) = serve(Settings::default());
+        let mut alice = Client::join(addr, "alice");
+        let mut bob = Client::join(addr, "bob");
+        alice.expect("bob joined.");
+
+        alice.send("start");
+        alice.expect("Round 1 has started!");
+        bob.expect("Round 1 has started!");
+
+        bob.send(&wrong_guess(secret).to_string());
+        bob.expect("is too");
+        assert_eq!(alice.expect("bob:"), "  bob: 0 wins, 1 guesses this round");
+
+        alice.send(&secret.to_string());
+        let won = format!(
+            "alice found the secret {} in 1 guess & wins round 1!",
+            secret
+        );
+        alice.expect(&won);
+        bob.expect(&won);
+        assert_eq!(bob.expect("alice:"), "  alice: 1 win");
+        assert_eq!(bob.expect("bob:"), "  bob: 0 wins");
+
+        bob.send(&secret.to_string());
+        bob.expect("No round is under way");
+    }
+
+    #[test]
+    fn round_ends_when_everyone_is_out_of_guesses() {
+        let settings = Settings {
+            max_guesses: Some(1),
+            ..Settings::default()
+        };
+        let (addr, secret) = serve(settings);
+        let mut carol = Client::join(addr, "carol");
+
+        carol.send("start");
+        carol.send("banana");
+        carol.expect("Please guess a number in [0, 100]");
+        carol.send(&wrong_guess(secret).to_string());
+        carol.expect("Out of guesses!");
+        assert_eq!(
+            carol.expect("carol:"),
+            "  carol: 0 wins, 1 guesses this round, out of guesses"
+        );
+        carol.expect(&format!("Nobody found the secret, it was {}.", secret));
+        carol.expect("Type 'start' to play another round.");
+    }
+
+    #[test]
+    fn names_are_unique_and_leaving_is_announced() {
+        let (addr, _) = serve(Settings::default());
+        let mut dave = Client::join(addr, "dave");
+
+        let mut impostor = TcpStream::connect(addr).unwrap();
+        let mut impostor_reader = BufReader::new(impostor.try_clone().unwrap());
+        impostor_reader.read_line(&mut String::new()).unwrap();
+        impostor_reader.read_line(&mut String::new()).unwrap();
+        write!(impostor, "dave\r\n").unwrap();
+        let mut line = String::new();
+        impostor_reader.read_line(&mut line).unwrap();
+        assert!(line.contains("taken"));
+
+        let mut erin = Client::join(addr, "erin");
+        dave.expect("erin joined.");
+        erin.send("quit");
+        erin.expect("Bye!");
+        dave.expect("erin left.");
+    }
+
+    #[test]
+    fn overlong_lines_disconnect_the_sender() {
+        let (addr, _) = serve(Settings::default());
+        let mut frank = Client::join(addr, "frank");
+        let mut gina = Client::join(addr, "gina");
+
+        gina.send(&"9".repeat(MAX_LINE + 1));
+        gina.expect("can't be longer than");
+        assert_eq!(gina.reader.read_line(&mut String::new()).unwrap(), 0);
+        frank.expect("gina left.");
+    }
+}
@@ -0,0 +1,672 @@
+use std::{
+    cell::RefCell,
+    io::{BufRead, Result, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    constants::{MAX_SECRET, MIN_SECRET},
+    difficulty::{prompt_custom, Difficulty, Settings},
+    game::{Game, GameError, GameState},
+    io::{prompt, write, WriteArgs},
+    menu::{Menu, MenuAction},
+    players::Strategy,
+    random::{NumberGenerator, SecretSource},
+    recording::Recording,
+    reverse::play_reverse,
+    save::SavedGame,
+    scores::{Outcome, ScoreRecord, Scoreboard},
+    scoring::Score,
+    summary::{Round, Summary},
+};
+
+/// Everything an interactive session keeps track of across rounds.
+pub struct Session {
+    pub player: String,
+    /// Seed the session's secrets come from
+    pub seed: u64,
+    pub scoreboard: Scoreboard,
+    pub summary: Summary,
+    /// File every round is recorded to, if any
+    pub record: Option<PathBuf>,
+}
+
+/// Run an interactive session on the given streams, looping over the main
+/// menu until the player exits. Every round is played with the given
+/// settings if any, otherwise at a difficulty chosen from a menu, & recorded
+/// on the scoreboard, unless it's saved to be resumed later. The player is
+/// asked their name unless it's given. Rounds are also recorded to the
+/// `record` file if given, see crate::recording.
+/// A Summary of the session's rounds is written when it ends.
+/// Returns Err if either stream fails, including when input runs out.
+pub fn run<W: Write, R: BufRead>(
+    settings: Option<Settings>,
+    player: Option<String>,
+    rnd: NumberGenerator,
+    scoreboard: Scoreboard,
+    record: Option<PathBuf>,
+    mut output: W,
+    mut input: R,
+) -> Result<()> {
+    let seed = rnd.seed();
+    let rnd = RefCell::new(rnd);
+    // secrets for the computer to guess come from the same range as the
+    // player's
+    let (min, max) = match settings {
+        Some(settings) => (settings.min, settings.max),
+        None => (MIN_SECRET, MAX_SECRET),
+    };
+
+    // greet the user
+    write(
+        &mut output,
+        WriteArgs::Str("Welcome to the guessing game!\n"),
+    )?;
+    write(
+        &mut output,
+        WriteArgs::Fmt(format_args!(
+            "Secrets this session come from seed {0}, play with --seed {0} to get them again.\n",
+            seed
+        )),
+    )?;
+    let player = match player {
+        Some(name) => name,
+        None => {
+            write(&mut output, WriteArgs::Str("\nWhat's your name?\n"))?;
+            match prompt(&mut output, &mut input)? {
+                name if name.is_empty() => String::from("anonymous"),
+                name => name,
+            }
+        }
+    };
+    let session = RefCell::new(Session {
+        player,
+        seed,
+        scoreboard,
+        summary: Summary::new(),
+        record,
+    });
+
+    // build the main menu, with submenus for picking a difficulty, unless
+    // every round is played with the given settings, & for picking who
+    // guesses in reverse mode
+    let mut main_menu = Menu::new();
+
+    match settings {
+        Some(settings) => {
+            let difficulty = Difficulty::Custom(settings);
+            let (rnd, session) = (&rnd, &session);
+            main_menu.add_option(
+                &format!("play game ({})", difficulty.describe()),
+                move |output, input| {
+                    play_game(
+                        difficulty,
+                        &mut *rnd.borrow_mut(),
+                        &mut session.borrow_mut(),
+                        output,
+                        input,
+                    )?;
+                    Ok(MenuAction::Stay)
+                },
+            );
+        }
+        None => {
+            let mut difficulty_menu = Menu::new();
+            difficulty_menu.set_intro("Choose a difficulty...");
+            for difficulty in Difficulty::PRESETS {
+                let (rnd, session) = (&rnd, &session);
+                difficulty_menu.add_option(&difficulty.describe(), move |output, input| {
+                    play_game(
+                        difficulty,
+                        &mut *rnd.borrow_mut(),
+                        &mut session.borrow_mut(),
+                        output,
+                        input,
+                    )?;
+                    Ok(MenuAction::Back)
+                });
+            }
+            difficulty_menu.add_option("custom", |output, input| {
+                let difficulty = prompt_custom(&mut *output, &mut *input)?;
+                play_game(
+                    difficulty,
+                    &mut *rnd.borrow_mut(),
+                    &mut session.borrow_mut(),
+                    output,
+                    input,
+                )?;
+                Ok(MenuAction::Back)
+            });
+            difficulty_menu.add_back("back");
+            main_menu.add_submenu("play game", difficulty_menu);
+        }
+    }
+
+    main_menu.add_option("resume saved game", |output, input| {
+        resume_game(&mut session.borrow_mut(), output, input)?;
+        Ok(MenuAction::Stay)
+    });
+
+    let mut reverse_menu = Menu::new();
+    reverse_menu.set_intro("Who should guess?");
+    for strategy in Strategy::ALL {
+        reverse_menu.add_option(strategy.name(), move |output, input| {
+            let mut guesser = strategy.player(min, max);
+            let reverse_result = play_reverse(min, max, &mut guesser, &mut *output, &mut *input);
+            match reverse_result {
+                Ok(guesses) => write(
+                    &mut *output,
+                    WriteArgs::Fmt(format_args!("Got it in {} guesses!\n", guesses)),
+                )?,
+                Err(GameError::Io(err)) => return Err(err),
+                Err(GameError::Contradiction) => write(&mut *output, WriteArgs::Str("No fair! "))?,
+                Err(_) => write(&mut *output, WriteArgs::Str("You quit. "))?,
+            }
+
+            write(output, WriteArgs::Str("Play again?\n"))?;
+            Ok(MenuAction::Back)
+        });
+    }
+    reverse_menu.add_back("back");
+    main_menu.add_submenu("computer guesses", reverse_menu);
+
+    main_menu.add_option("view high scores", |output, _| {
+        session.borrow().scoreboard.render(output)?;
+        Ok(MenuAction::Stay)
+    });
+
+    main_menu.add_option("exit", |_, _| Ok(MenuAction::Exit));
+
+    // enter loop, summing up the session however it ends
+    let result = main_menu.run(&mut output, &mut input);
+    session.borrow().summary.render(&mut output)?;
+    result.map(|_| ())
+}
+
+/// Play a round at the given difficulty with a human guesser, tell them how
+/// it went & record it on the scoreboard, in the session's Summary & in the
+/// session's recording if any. A round the player saves, or leaves by
+/// running out of input or exiting after Ctrl-C, is saved next to the
+/// scoreboard to be resumed later, & any error is returned once it's saved.
+/// A round saved earlier is only replaced if the player says so when
+/// saving, otherwise the new round is recorded as quit.
+pub fn play_game(
+    difficulty: Difficulty,
+    rnd: &mut impl SecretSource,
+    session: &mut Session,
+    output: &mut impl Write,
+    input: &mut impl BufRead,
+) -> Result<()> {
+    let settings = difficulty.settings();
+    let secret = rnd.gen_secret_in(settings.min, settings.max);
+
+    write(
+        &mut *output,
+        WriteArgs::Fmt(format_args!("\nPlaying {}\n", difficulty.describe())),
+    )?;
+
+    let round = SavedGame {
+        difficulty,
+        state: GameState::new(secret, settings),
+        times: Vec::new(),
+        elapsed: Duration::ZERO,
+    };
+    play_from(round, session, output, input)
+}
+
+/// Resume the round saved next to the scoreboard, if there is one, picking
+/// it up where it stopped & carrying on as `play_game` does. The save is
+/// removed once loaded, so a round can't be finished twice.
+pub fn resume_game(
+    session: &mut Session,
+    output: &mut impl Write,
+    input: &mut impl BufRead,
+) -> Result<()> {
+    let path = SavedGame::path_for(session.scoreboard.path());
+    let loaded = SavedGame::load(&path).and_then(|round| SavedGame::remove(&path).map(|_| round));
+    let round = match loaded {
+        Ok(Some(round)) => round,
+        Ok(None) => return write(output, WriteArgs::Str("There's no saved game to resume.\n")),
+        Err(err) => {
+            return write(
+                output,
+                WriteArgs::Fmt(format_args!(
+                    "Couldn't resume the game saved in {}: {}\n",
+                    path.display(),
+                    err
+                )),
+            )
+        }
+    };
+
+    let (low, high) = round.state.interval();
+    write(
+        &mut *output,
+        WriteArgs::Fmt(format_args!(
+            "\nResuming {}. {} guesses made so far, the secret is in [{}, {}].\n",
+            round.difficulty.describe(),
+            round.state.guesses(),
+            low,
+            high
+        )),
+    )?;
+
+    play_from(round, session, output, input)
+}
+
+/// Play a round on from the given state, then record or save it.
+fn play_from(
+    round: SavedGame,
+    session: &mut Session,
+    output: &mut impl Write,
+    input: &mut impl BufRead,
+) -> Result<()> {
+    let difficulty = round.difficulty;
+    let mut game = Game::from_state(
+        round.state,
+        round.times,
+        round.elapsed,
+        &mut *output,
+        &mut *input,
+    );
+    let result = game.resume();
+    let state = game.state().clone();
+    let times = game.times().to_vec();
+    let duration = game.elapsed();
+
+    // the round decides how it went, even if telling the player failed, &
+    // unfinished rounds are left to be picked up later, rather than recording
+    // them as quit, unless that would replace a round saved earlier
+    let outcome = if state.is_won() {
+        Some(Outcome::Won)
+    } else if state.is_over() {
+        Some(Outcome::Lost)
+    } else {
+        match result {
+            Err(GameError::Saved) | Err(GameError::Io(_)) => None,
+            _ => Some(Outcome::Quit),
+        }
+    };
+    if let (None, Err(GameError::Io(_))) = (outcome, &result) {
+        // left mid-prompt
+        write(&mut *output, WriteArgs::Str("\n"))?;
+    }
+    let save_path = SavedGame::path_for(session.scoreboard.path());
+    let ask = matches!(result, Err(GameError::Saved));
+    let outcome = match outcome {
+        None if !may_replace_save(&save_path, ask, &mut *output, &mut *input)? => {
+            write(
+                &mut *output,
+                WriteArgs::Str("Kept the round saved earlier, this one counts as quit.\n"),
+            )?;
+            Some(Outcome::Quit)
+        }
+        outcome => outcome,
+    };
+
+    if let Some(path) = &session.record {
+        let recording = Recording::new(session.seed, difficulty, &state, &times, outcome, duration);
+        if let Err(err) = recording.append(path) {
+            write(
+                &mut *output,
+                WriteArgs::Fmt(format_args!(
+                    "\nCouldn't record the round to {}: {}\n",
+                    path.display(),
+                    err
+                )),
+            )?;
+        }
+    }
+
+    let outcome = match outcome {
+        Some(outcome) => outcome,
+        None => {
+            let saved = SavedGame {
+                difficulty,
+                state,
+                times,
+                elapsed: duration,
+            }
+            .save(&save_path);
+            match saved {
+                Ok(()) => write(
+                    &mut *output,
+                    WriteArgs::Str(
+                        "Your round is saved, choose \"resume saved game\" to finish it.\n",
+                    ),
+                )?,
+                Err(err) => write(
+                    &mut *output,
+                    WriteArgs::Fmt(format_args!(
+                        "Couldn't save your round to {}: {}\n",
+                        save_path.display(),
+                        err
+                    )),
+                )?,
+            }
+            return match result {
+                Err(GameError::Io(err)) => Err(err),
+                _ => write(output, WriteArgs::Str("Play again?\n")),
+            };
+        }
+    };
+
+    let settings = state.settings();
+    let secret = state.secret();
+    let guesses = state.guesses();
+    let score = Score::new(&state, duration);
+
+    let record = ScoreRecord {
+        player: session.player.clone(),
+        difficulty: difficulty.name().to_string(),
+        min: settings.min,
+        max: settings.max,
+        guesses,
+        duration,
+        outcome,
+        score: score.total,
+        hints: state.hints().len(),
+    };
+    let saved = session.scoreboard.add(record);
+    session.summary.add(Round {
+        settings,
+        secret,
+        guesses,
+        duration,
+        outcome,
+    });
+
+    match (outcome, result) {
+        (_, Err(GameError::Io(err))) => return Err(err),
+        (Outcome::Won, _) => write(
+            &mut *output,
+            WriteArgs::Fmt(format_args!("You won! {}\n", score.describe())),
+        )?,
+        (Outcome::Lost, _) => write(
+            &mut *output,
+            WriteArgs::Fmt(format_args!(
+                "Out of guesses! The number was {}. You lost. ",
+                secret
+            )),
+        )?,
+        (Outcome::Quit, Err(GameError::Quit)) | (Outcome::Quit, Err(GameError::Saved)) => {
+            write(&mut *output, WriteArgs::Str("You quit. "))?
+        }
+        (Outcome::Quit, _) => write(&mut *output, WriteArgs::Str("An unknown Error occurred."))?,
+    }
+
+    if let Err(err) = saved {
+        write(
+            &mut *output,
+            WriteArgs::Fmt(format_args!(
+                "\nCouldn't save score to {}: {}\n",
+                session.scoreboard.path().display(),
+                err
+            )),
+        )?;
+    }
+
+    write(output, WriteArgs::Str("Play again?\n"))
+}
+
+/// Whether a round may be saved to the given path, replacing the round
+/// saved there earlier, if any. Asks the player if `ask` is set, otherwise,
+/// e.g. when they've left the game, the earlier round is kept.
+fn may_replace_save(
+    path: &Path,
+    ask: bool,
+    output: &mut impl Write,
+    input: &mut impl BufRead,
+) -> Result<bool> {
+    let earlier = match SavedGame::load(path) {
+        Ok(Some(earlier)) => earlier,
+        // nothing there that could be resumed
+        _ => return Ok(true),
+    };
+    if !ask {
+        return Ok(false);
+    }
+
+    write(
+        &mut *output,
+        WriteArgs::Fmt(format_args!(
+            "There's already a saved round, {} with {} guesses made. Replace it with this one (r) or quit this one (q)?\n",
+            earlier.difficulty.describe(),
+            earlier.state.guesses()
+        )),
+    )?;
+    loop {
+        match prompt(&mut *output, &mut *input) {
+            Ok(answer) => match answer.as_str() {
+                "r" => return Ok(true),
+                "q" => return Ok(false),
+                _ => write(&mut *output, WriteArgs::Str("Please enter r or q.\n"))?,
+            },
+            // e.g. Ctrl-C, keep the earlier round rather than guess
+            Err(_) => return Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, io::ErrorKind, path::PathBuf};
+
+    use crate::io::{
+        is_eof, is_interrupt,
+        test_utils::{setup_io_with_many_inputs, INTERRUPT},
+    };
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir()
+            .join(format!("guessing_game_session_test_{}", std::process::id()))
+            .join(name)
+    }
+
+    fn one_secret(secret: usize) -> Option<Settings> {
+        Some(Settings {
+            min: secret,
+            max: secret,
+            max_guesses: None,
+        })
+    }
+
+    #[test]
+    fn run_plays_a_round_records_it_and_exits() {
+        let path = temp_path("played.tsv");
+        let scoreboard = Scoreboard::load(&path).unwrap();
+        // play game, guess the only possible secret, exit
+        let inputs = ["1", "5", "5"];
+        let (mut writer, reader) = setup_io_with_many_inputs(&inputs);
+        let rnd = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, 1);
+        let player = Some(String::from("ada"));
+
+        run(
+            one_secret(5),
+            player,
+            rnd,
+            scoreboard,
+            None,
+            &mut writer,
+            reader,
+        )
+        .unwrap();
+
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.starts_with("You won!")));
+        let records = Scoreboard::load(&path).unwrap().records().to_vec();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].player, "ada");
+        assert_eq!(records[0].outcome, Outcome::Won);
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains("This session: 1 played, 1 won")));
+    }
+
+    #[test]
+    fn run_returns_eof_if_input_runs_out() {
+        let scoreboard = Scoreboard::load(temp_path("eof.tsv")).unwrap();
+        let (mut writer, reader) = setup_io_with_many_inputs(&["ada"]);
+        let rnd = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, 1);
+        let err = run(None, None, rnd, scoreboard, None, &mut writer, reader).unwrap_err();
+
+        assert!(is_eof(&err));
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains("No games played this session.")));
+    }
+
+    #[test]
+    fn exiting_after_ctrl_c_saves_the_round_to_resume() {
+        // a directory of its own, as the save is kept next to the scoreboard
+        let path = temp_path("interrupted").join("scores.tsv");
+        let record = temp_path("interrupted").join("rounds.log");
+        let scoreboard = Scoreboard::load(&path).unwrap();
+        let settings = Settings {
+            min: 4,
+            max: 5,
+            max_guesses: None,
+        };
+        let secret = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, 1).gen_secret_in(4, 5);
+        let (wrong, secret) = ((9 - secret).to_string(), secret.to_string());
+        // play game, guess wrong, Ctrl-C & exit
+        let inputs = ["1", &wrong, INTERRUPT, "x"];
+        let (mut writer, reader) = setup_io_with_many_inputs(&inputs);
+        let rnd = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, 1);
+        let player = Some(String::from("ada"));
+        let err = run(
+            Some(settings),
+            player,
+            rnd,
+            scoreboard,
+            Some(record.clone()),
+            &mut writer,
+            reader,
+        )
+        .unwrap_err();
+
+        assert!(is_interrupt(&err));
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains("Your round is saved")));
+        assert!(Scoreboard::load(&path).unwrap().records().is_empty());
+
+        // resume, guess right, exit
+        let scoreboard = Scoreboard::load(&path).unwrap();
+        let (mut writer, reader) = setup_io_with_many_inputs(&["2", &secret, &secret]);
+        let rnd = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, 1);
+        let player = Some(String::from("ada"));
+        run(
+            None,
+            player,
+            rnd,
+            scoreboard,
+            Some(record.clone()),
+            &mut writer,
+            reader,
+        )
+        .unwrap();
+
+        let status = format!("1 guesses made so far, the secret is in [{0}, {0}]", secret);
+        assert!(writer
+            .written_lines
+            .iter()
+            .any(|line| line.contains(&status)));
+        let records = Scoreboard::load(&path).unwrap().records().to_vec();
+        assert_eq!(records[0].outcome, Outcome::Won);
+        assert_eq!(records[0].guesses, 2);
+        assert_eq!(SavedGame::load(SavedGame::path_for(&path)).unwrap(), None);
+
+        // both sittings are recorded
+        let outcomes: Vec<_> = Recording::load_all(&record)
+            .unwrap()
+            .iter()
+            .map(|r| (r.outcome, r.feedback().len()))
+            .collect();
+        assert_eq!(outcomes, [(None, 1), (Some(Outcome::Won), 2)]);
+    }
+
+    #[test]
+    fn a_won_round_counts_even_if_telling_the_player_fails() {
+        // a writer that breaks once the game tells the player they're right
+        struct BreaksOnWin;
+
+        impl Write for BreaksOnWin {
+            fn write(&mut self, buf: &[u8]) -> Result<usize> {
+                if buf.starts_with(b"Correct!") {
+                    return Err(std::io::Error::from(ErrorKind::BrokenPipe));
+                }
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let path = temp_path("broken").join("scores.tsv");
+        let mut session = Session {
+            player: String::from("ada"),
+            seed: 1,
+            scoreboard: Scoreboard::load(&path).unwrap(),
+            summary: Summary::new(),
+            record: None,
+        };
+        let settings = one_secret(5).unwrap();
+        let round = SavedGame {
+            difficulty: Difficulty::Custom(settings),
+            state: GameState::new(5, settings),
+            times: Vec::new(),
+            elapsed: Duration::ZERO,
+        };
+        let (_, mut reader) = setup_io_with_many_inputs(&["5"]);
+        let err = play_from(round, &mut session, &mut BreaksOnWin, &mut reader).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+        let records = Scoreboard::load(&path).unwrap().records().to_vec();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].outcome, Outcome::Won);
+        assert_eq!(SavedGame::load(SavedGame::path_for(&path)).unwrap(), None);
+    }
+
+    #[test]
+    fn saving_asks_before_replacing_an_earlier_save() {
+        let path = temp_path("replace").join("scores.tsv");
+        let scoreboard = Scoreboard::load(&path).unwrap();
+        // save a round, then another keeping the first, then replace it
+        let inputs = ["1", "/save", "1", "/save", "q", "1", "/save", "r"];
+        let (mut writer, reader) = setup_io_with_many_inputs(&inputs);
+        let rnd = NumberGenerator::with_seed(MIN_SECRET, MAX_SECRET, 1);
+        let player = Some(String::from("ada"));
+        let err = run(
+            one_secret(5),
+            player,
+            rnd,
+            scoreboard,
+            None,
+            &mut writer,
+            reader,
+        )
+        .unwrap_err();
+
+        assert!(is_eof(&err));
+        let written = writer.written_lines.concat();
+        assert_eq!(written.matches("There's already a saved round").count(), 2);
+        assert_eq!(written.matches("Your round is saved").count(), 2);
+        assert!(written.contains("Kept the round saved earlier"));
+        let records = Scoreboard::load(&path).unwrap().records().to_vec();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].outcome, Outcome::Quit);
+        assert!(SavedGame::load(SavedGame::path_for(&path))
+            .unwrap()
+            .is_some());
+    }
+}
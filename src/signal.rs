@@ -0,0 +1,113 @@
+use std::{
+    io::{BufRead, ErrorKind, Read, Result},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::io::interrupted;
+
+/// Set by the SIGINT handler, & cleared once a read stream reports it.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Catch Ctrl-C instead of letting it kill the process. A read blocked on
+/// the terminal when Ctrl-C is pressed fails, rather than being restarted,
+/// so an Interruptible stream can report it. Does nothing on platforms
+/// without signals.
+#[cfg(unix)]
+pub fn install() -> Result<()> {
+    extern "C" fn handle(_: libc::c_int) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+
+    // SAFETY: the handler only stores to an atomic, which is
+    // async-signal-safe, & the sigaction struct is fully initialised
+    // before it's passed in
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handle as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        // no SA_RESTART, so a blocked read returns EINTR
+        action.sa_flags = 0;
+        libc::sigemptyset(&mut action.sa_mask);
+        if libc::sigaction(libc::SIGINT, &action, std::ptr::null_mut()) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn install() -> Result<()> {
+    Ok(())
+}
+
+/// Whether Ctrl-C has been pressed since this was last called.
+pub fn take_interrupt() -> bool {
+    INTERRUPTED.swap(false, Ordering::SeqCst)
+}
+
+/// A read stream that fails with crate::io::interrupted once Ctrl-C is
+/// pressed, e.g. wrapping a locked stdin.
+pub struct Interruptible<R> {
+    inner: R,
+}
+
+impl<R> Interruptible<R> {
+    pub fn new(inner: R) -> Self {
+        Interruptible { inner }
+    }
+
+    /// Report Ctrl-C if it was pressed, otherwise retry reads the signal
+    /// only interrupted.
+    fn retry<T>(&mut self, mut read: impl FnMut(&mut R) -> Result<T>) -> Result<T> {
+        loop {
+            if take_interrupt() {
+                return Err(interrupted());
+            }
+            match read(&mut self.inner) {
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for Interruptible<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.retry(|inner| inner.read(buf))
+    }
+}
+
+impl<R: BufRead> BufRead for Interruptible<R> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        // check before borrowing the buffer, which can't be returned from
+        // inside the retry loop
+        self.retry(|inner| inner.fill_buf().map(|_| ()))?;
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::io::is_interrupt;
+
+    use super::*;
+
+    #[test]
+    fn interruptible_reports_ctrl_c_once() {
+        let mut reader = Interruptible::new(Cursor::new("42\n"));
+        let mut line = String::new();
+
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        let err = reader.read_line(&mut line).unwrap_err();
+        assert!(is_interrupt(&err));
+
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "42\n");
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    cmp::Ordering,
+    io::{Result, Write},
+    time::Duration,
+};
+
+use crate::{
+    difficulty::Settings,
+    game::play_with,
+    io::{write, WriteArgs},
+    players::BinaryPlayer,
+    scores::Outcome,
+};
+
+/// One round played during a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Round {
+    pub settings: Settings,
+    pub secret: usize,
+    pub guesses: usize,
+    pub duration: Duration,
+    pub outcome: Outcome,
+}
+
+impl Round {
+    /// Guesses a binary search would have needed to find this round's
+    /// secret.
+    pub fn binary_guesses(&self) -> usize {
+        let mut guesser = BinaryPlayer::new(self.settings.min, self.settings.max);
+        play_with(self.secret, &mut guesser).expect("binary search always finds the secret")
+    }
+}
+
+/// The rounds played in an interactive session, summed up when it ends.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Summary {
+    rounds: Vec<Round>,
+}
+
+impl Summary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a finished round to the summary.
+    pub fn add(&mut self, round: Round) {
+        self.rounds.push(round);
+    }
+
+    pub fn rounds(&self) -> &[Round] {
+        &self.rounds
+    }
+
+    /// Number of rounds that ended with the given outcome.
+    pub fn count(&self, outcome: Outcome) -> usize {
+        self.rounds.iter().filter(|r| r.outcome == outcome).count()
+    }
+
+    /// Average guesses taken to win a round, if any were won.
+    pub fn average_guesses(&self) -> Option<f64> {
+        let wins = self.count(Outcome::Won);
+        match wins {
+            0 => None,
+            _ => Some(self.won().map(|r| r.guesses).sum::<usize>() as f64 / wins as f64),
+        }
+    }
+
+    /// Fewest guesses taken to win a round, if any were won.
+    pub fn best_guesses(&self) -> Option<usize> {
+        self.won().map(|r| r.guesses).min()
+    }
+
+    /// Time spent playing rounds, not counting time spent in menus.
+    pub fn total_time(&self) -> Duration {
+        self.rounds.iter().map(|r| r.duration).sum()
+    }
+
+    /// Guesses taken to win every won round, & the guesses a binary search
+    /// would have needed for the same secrets.
+    pub fn against_binary_search(&self) -> (usize, usize) {
+        self.won().fold((0, 0), |(taken, binary), r| {
+            (taken + r.guesses, binary + r.binary_guesses())
+        })
+    }
+
+    fn won(&self) -> impl Iterator<Item = &Round> {
+        self.rounds.iter().filter(|r| r.outcome == Outcome::Won)
+    }
+
+    /// Write the summary to the write stream.
+    pub fn render<W: Write>(&self, mut writer: W) -> Result<()> {
+        if self.rounds.is_empty() {
+            return write(writer, WriteArgs::Str("\nNo games played this session.\n"));
+        }
+
+        write(
+            &mut writer,
+            WriteArgs::Fmt(format_args!(
+                "\nThis session: {} played, {} won, {} lost, {} quit, {:.1}s spent guessing.\n",
+                self.rounds.len(),
+                self.count(Outcome::Won),
+                self.count(Outcome::Lost),
+                self.count(Outcome::Quit),
+                self.total_time().as_secs_f64(),
+            )),
+        )?;
+
+        if let (Some(average), Some(best)) = (self.average_guesses(), self.best_guesses()) {
+            let (taken, binary) = self.against_binary_search();
+            let verdict = match taken.cmp(&binary) {
+                Ordering::Less => "beat",
+                Ordering::Equal => "matched",
+                Ordering::Greater => "trailed",
+            };
+            write(
+                &mut writer,
+                WriteArgs::Fmt(format_args!(
+                    "Wins took {:.1} guesses on average, {} at best.\n",
+                    average, best
+                )),
+            )?;
+            write(
+                writer,
+                WriteArgs::Fmt(format_args!(
+                    "You {} a binary search, taking {} guesses where it would have taken {}.\n",
+                    verdict, taken, binary
+                )),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::test_utils::setup_io;
+
+    use super::*;
+
+    fn round(secret: usize, guesses: usize, outcome: Outcome) -> Round {
+        Round {
+            settings: Settings {
+                min: 1,
+                max: 100,
+                max_guesses: None,
+            },
+            secret,
+            guesses,
+            duration: Duration::from_secs(10),
+            outcome,
+        }
+    }
+
+    #[test]
+    fn summary_counts_outcomes_and_guesses() {
+        let mut summary = Summary::new();
+        summary.add(round(50, 1, Outcome::Won));
+        summary.add(round(10, 8, Outcome::Won));
+        summary.add(round(10, 3, Outcome::Quit));
+
+        assert_eq!(summary.count(Outcome::Won), 2);
+        assert_eq!(summary.count(Outcome::Quit), 1);
+        assert_eq!(summary.average_guesses(), Some(4.5));
+        assert_eq!(summary.best_guesses(), Some(1));
+        assert_eq!(summary.total_time(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn only_won_rounds_are_compared_with_binary_search() {
+        let mut summary = Summary::new();
+        // binary search over [1, 100] guesses 50 first
+        summary.add(round(50, 3, Outcome::Won));
+        summary.add(round(10, 3, Outcome::Quit));
+
+        assert_eq!(summary.against_binary_search(), (3, 1));
+    }
+
+    #[test]
+    fn render_without_rounds_says_so() {
+        let (mut writer, _) = setup_io();
+        Summary::new().render(&mut writer).unwrap();
+
+        assert_eq!(writer.written_lines, ["\nNo games played this session.\n"]);
+    }
+}